@@ -0,0 +1,77 @@
+use cipher::Cipher;
+
+// affine cipher over a-z: every letter x becomes (a * x + b) mod 26.
+// Like ShiftCipher it keeps the case of letters and passes anything else through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    a: usize,
+    b: usize,
+    a_inverse: usize,
+}
+
+impl Affine {
+    // `a` should be coprime with 26, otherwise letters can't be decrypted
+    pub fn new(a: isize, b: isize) -> Result<Affine, String> {
+        let a = a.rem_euclid(26) as usize;
+        let b = b.rem_euclid(26) as usize;
+
+        match (1..26).find(|i| (a * i) % 26 == 1) {
+            Some(a_inverse) => Ok(Affine { a, b, a_inverse }),
+            None => Err(format!("affine key a should be coprime with 26, got {}", a)),
+        }
+    }
+
+    fn map(&self, input: &str, f: &dyn Fn(usize) -> usize) -> String {
+        input.chars()
+            .map(|c| {
+                if !c.is_ascii_alphabetic() {
+                    return c;
+                }
+
+                let base = if c.is_ascii_uppercase() { b'A' } else { b'a' };
+                (base + f((c as u8 - base) as usize) as u8) as char
+            })
+            .collect()
+    }
+}
+
+impl Cipher for Affine {
+    fn encrypt(&self, input: &str) -> String {
+        self.map(input, &|x| (self.a * x + self.b) % 26)
+    }
+
+    fn decrypt(&self, input: &str) -> String {
+        self.map(input, &|y| (self.a_inverse * (y + 26 - self.b)) % 26)
+    }
+}
+
+#[cfg(test)]
+mod new {
+    use affine::Affine;
+
+    #[test]
+    fn it_rejects_keys_without_inverse() {
+        for a in &[0, 2, 13, 26] {
+            assert!(Affine::new(*a, 1).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod encrypt {
+    use affine::Affine;
+    use cipher::Cipher;
+
+    #[test]
+    fn it_encrypts_letters() {
+        let affine = Affine::new(5, 8).unwrap();
+        assert_eq!(affine.encrypt("Affine cipher"), "Ihhwvc swfrcp");
+    }
+
+    #[test]
+    fn it_roundtrips() {
+        let affine = Affine::new(7, -3).unwrap();
+        let input = "northpole object storage 548!";
+        assert_eq!(affine.decrypt(&affine.encrypt(input)), input);
+    }
+}
@@ -0,0 +1,86 @@
+use room::Room;
+use affine::Affine;
+use std::str::FromStr;
+
+pub trait Cipher {
+    fn encrypt(&self, input: &str) -> String;
+    fn decrypt(&self, input: &str) -> String;
+}
+
+// which cipher to decipher room names with, picked on the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CipherKind {
+    // shift keyed from the sector id, the original kiosk cipher
+    Caesar,
+    // keyed from the room's checksum
+    Vigenere,
+    // one fixed key for all rooms
+    Affine(Affine),
+}
+
+impl CipherKind {
+    pub fn cipher_for(&self, room: &Room) -> Box<dyn Cipher> {
+        match *self {
+            CipherKind::Caesar => Box::new(room.caesar()),
+            CipherKind::Vigenere => Box::new(room.vigenere()),
+            CipherKind::Affine(affine) => Box::new(affine),
+        }
+    }
+}
+
+// parses `caesar`, `vigenere` or `affine:<a>,<b>`
+impl FromStr for CipherKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("caesar"), None) => Ok(CipherKind::Caesar),
+            (Some("vigenere"), None) => Ok(CipherKind::Vigenere),
+            (Some("affine"), Some(key)) => {
+                let key : Vec<&str> = key.split(',').collect();
+                if key.len() != 2 {
+                    return Err(format!("affine key should look like <a>,<b>, got '{}'", key.join(",")));
+                }
+
+                let a = key[0].trim().parse().map_err(|_| format!("invalid affine key a '{}'", key[0]))?;
+                let b = key[1].trim().parse().map_err(|_| format!("invalid affine key b '{}'", key[1]))?;
+                Ok(CipherKind::Affine(Affine::new(a, b)?))
+            }
+            _ => Err(format!("unknown cipher '{}', expected caesar, vigenere or affine:<a>,<b>", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse_cipher_kind {
+    use cipher::CipherKind;
+    use affine::Affine;
+
+    #[test]
+    fn it_parses_known_ciphers() {
+        assert_eq!("caesar".parse(), Ok(CipherKind::Caesar));
+        assert_eq!("vigenere".parse(), Ok(CipherKind::Vigenere));
+        assert_eq!("affine:5,8".parse(), Ok(CipherKind::Affine(Affine::new(5, 8).unwrap())));
+    }
+
+    #[test]
+    fn it_rejects_unknown_ciphers() {
+        assert!("rot13".parse::<CipherKind>().is_err());
+        assert!("affine".parse::<CipherKind>().is_err());
+        assert!("affine:5".parse::<CipherKind>().is_err());
+        assert!("affine:2,3".parse::<CipherKind>().is_err());
+    }
+}
+
+#[cfg(test)]
+mod cipher_for {
+    use cipher::CipherKind;
+    use room::Room;
+
+    #[test]
+    fn it_picks_the_room_key() {
+        let room = Room::from_line("qzmt-zixmtkozy-ivhz-343[zimth]");
+        let cipher = CipherKind::Caesar.cipher_for(&room);
+        assert_eq!(room.decipher(&*cipher), "very encrypted name ");
+    }
+}
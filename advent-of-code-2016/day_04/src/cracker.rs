@@ -0,0 +1,145 @@
+use cipher::Cipher;
+use shift_cipher::{ ShiftCipher, LOWERCASE_ALPHABET };
+use std::cmp::Ordering;
+
+// relative frequency of a-z in english text
+const ENGLISH : [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966,
+    0.00153, 0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987,
+    0.06327, 0.09056, 0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+// expected distribution of letters in a deciphered name
+#[derive(Debug, Clone)]
+pub struct LetterFrequencies {
+    frequencies: [f64; 26],
+}
+
+impl LetterFrequencies {
+    pub fn english() -> LetterFrequencies {
+        LetterFrequencies { frequencies: ENGLISH }
+    }
+
+    // learn frequencies from a word list. Every letter starts with a count
+    // of one so letters missing from the list don't divide by zero later on
+    pub fn from_words<'a, I: IntoIterator<Item = &'a str>>(words : I) -> LetterFrequencies {
+        let mut counts = [1.0; 26];
+        for word in words {
+            for idx in word.chars().filter_map(letter_index) {
+                counts[idx] += 1.0;
+            }
+        }
+
+        let total : f64 = counts.iter().sum();
+        let mut frequencies = [0.0; 26];
+        for (frequency, count) in frequencies.iter_mut().zip(counts.iter()) {
+            *frequency = count / total;
+        }
+
+        LetterFrequencies { frequencies }
+    }
+
+    // chi-squared distance between the letters in `text` and these frequencies,
+    // the lower the score the more alike they are
+    pub fn chi_squared(&self, text : &str) -> f64 {
+        let mut observed = [0.0; 26];
+        for idx in text.chars().filter_map(letter_index) {
+            observed[idx] += 1.0;
+        }
+
+        let total : f64 = observed.iter().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+
+        observed.iter().zip(self.frequencies.iter())
+            .map(|(o, f)| {
+                let expected = f * total;
+                (o - expected).powi(2) / expected
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    // forward rotation that deciphers the name, i.e. the sector id modulo 26
+    pub shift: usize,
+    pub name: String,
+    pub score: f64,
+}
+
+// try every shift on an encrypted (dashed) room name and rank the
+// results by chi-squared score, most likely candidate first
+pub fn crack(encrypted_name : &str, frequencies : &LetterFrequencies) -> Vec<Candidate> {
+    let undashed_name = encrypted_name.trim_matches('-').replace('-', " ").to_lowercase();
+
+    let mut candidates : Vec<Candidate> = (0..LOWERCASE_ALPHABET.len())
+        .map(|shift| {
+            let name = ShiftCipher::new(shift as isize).encrypt(&undashed_name);
+            let score = frequencies.chi_squared(&name);
+            Candidate { shift, name, score }
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal).then(a.shift.cmp(&b.shift))
+    });
+
+    candidates
+}
+
+fn letter_index(c : char) -> Option<usize> {
+    if c.is_ascii_alphabetic() {
+        Some((c.to_ascii_lowercase() as u8 - b'a') as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod chi_squared {
+    use cracker::LetterFrequencies;
+
+    #[test]
+    fn it_prefers_english_over_gibberish() {
+        let english = LetterFrequencies::english();
+        assert!(english.chi_squared("northpole object storage") < english.chi_squared("lmprfnmjc mzhcar qrmpyec"));
+    }
+
+    #[test]
+    fn it_ignores_anything_but_letters() {
+        let english = LetterFrequencies::english();
+        assert_eq!(english.chi_squared("a-b c!"), english.chi_squared("abc"));
+        assert_eq!(english.chi_squared("123"), 0.0);
+    }
+}
+
+#[cfg(test)]
+mod crack {
+    use cracker::{ crack, LetterFrequencies };
+
+    #[test]
+    fn it_finds_the_shift_using_english_frequencies() {
+        let candidates = crack("lmprfnmjc-mzhcar-qrmpyec-", &LetterFrequencies::english());
+
+        assert_eq!(candidates.len(), 26);
+        assert_eq!(candidates[0].shift, 548 % 26);
+        assert_eq!(candidates[0].name, "northpole object storage");
+    }
+
+    #[test]
+    fn it_finds_the_shift_using_a_word_list() {
+        let words = vec!["very", "encrypted", "name", "sector"];
+        let candidates = crack("qzmt-zixmtkozy-ivhz", &LetterFrequencies::from_words(words));
+
+        assert_eq!(candidates[0].shift, 343 % 26);
+        assert_eq!(candidates[0].name, "very encrypted name");
+    }
+
+    #[test]
+    fn it_ranks_candidates_by_score() {
+        let candidates = crack("qzmt-zixmtkozy-ivhz", &LetterFrequencies::english());
+        assert!(candidates.windows(2).all(|w| w[0].score <= w[1].score));
+    }
+}
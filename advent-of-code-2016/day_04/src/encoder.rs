@@ -0,0 +1,135 @@
+use room::checksum;
+use cipher::Cipher;
+use shift_cipher::ShiftCipher;
+use std::str::FromStr;
+
+// ways to break the checksum of a decoy room
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decoy {
+    // swap the first two checksum letters
+    Swapped,
+    // rotate every checksum character by one, through a-z and then 0-9
+    Shifted,
+}
+
+impl FromStr for Decoy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "swapped" => Ok(Decoy::Swapped),
+            "shifted" => Ok(Decoy::Shifted),
+            _ => Err(format!("unknown decoy '{}', expected swapped or shifted", s)),
+        }
+    }
+}
+
+// encrypt a plaintext room name into a line `Room::from_line` accepts,
+// e.g. "very encrypted name" with 343 becomes "qzmt-zixmtkozy-ivhz-343[zimth]"
+pub fn encode(plaintext : &str, sector_id : isize) -> Result<String, String> {
+    let name = encrypt_name(plaintext, sector_id)?;
+    let checksum = checksum(&name);
+    Ok(format!("{}{}[{}]", name, sector_id, checksum))
+}
+
+// same as `encode`, but with a checksum that is guaranteed to be wrong
+pub fn encode_decoy(plaintext : &str, sector_id : isize, decoy : Decoy) -> Result<String, String> {
+    let name = encrypt_name(plaintext, sector_id)?;
+    let checksum = break_checksum(&checksum(&name), decoy);
+    Ok(format!("{}{}[{}]", name, sector_id, checksum))
+}
+
+// encrypts the words and joins them with dashes, including the trailing one
+fn encrypt_name(plaintext : &str, sector_id : isize) -> Result<String, String> {
+    if sector_id < 0 {
+        return Err(format!("sector id should not be negative, got {}", sector_id));
+    }
+
+    let words : Vec<String> = plaintext.split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    if words.is_empty() {
+        return Err("room name should contain at least one word".to_string());
+    }
+
+    if let Some(c) = words.iter().flat_map(|w| w.chars()).find(|c| !c.is_ascii_alphanumeric()) {
+        return Err(format!("room names can only contain letters and digits, found '{}'", c));
+    }
+
+    // decrypting rotates forward by the sector id, so encrypting rotates back
+    let cipher = ShiftCipher::new(-sector_id);
+    let name : String = words.iter()
+        .map(|w| format!("{}-", cipher.encrypt(w)))
+        .collect();
+
+    Ok(name)
+}
+
+// the correct checksum is unique, so any other arrangement is a decoy
+fn break_checksum(checksum : &str, decoy : Decoy) -> String {
+    let mut chars : Vec<char> = checksum.chars().collect();
+
+    match decoy {
+        Decoy::Swapped if chars.len() > 1 => {
+            chars.swap(0, 1);
+            chars.into_iter().collect()
+        }
+        // rotating changes every character, digits included, so even a
+        // single character checksum can't stay the same
+        _ => chars.into_iter().map(rotate).collect(),
+    }
+}
+
+fn rotate(c : char) -> char {
+    match c {
+        'z' => '0',
+        '9' => 'a',
+        _ => (c as u8 + 1) as char,
+    }
+}
+
+#[cfg(test)]
+mod encode {
+    use encoder::encode;
+    use room::Room;
+
+    #[test]
+    fn it_encodes_the_puzzle_example() {
+        assert_eq!(encode("very encrypted name", 343).unwrap(), "qzmt-zixmtkozy-ivhz-343[zimth]");
+    }
+
+    #[test]
+    fn it_roundtrips_through_room() {
+        let line = encode("Northpole Object Storage", 548).unwrap();
+        let room = Room::from_line(&line);
+
+        assert!(room.is_valid());
+        assert_eq!(room.sector_id, 548);
+        assert_eq!(room.decipher(&room.caesar()), "northpole object storage ");
+    }
+
+    #[test]
+    fn it_rejects_invalid_input() {
+        assert!(encode("", 1).is_err());
+        assert!(encode("   ", 1).is_err());
+        assert!(encode("north-pole", 1).is_err());
+        assert!(encode("north pole", -1).is_err());
+    }
+}
+
+#[cfg(test)]
+mod encode_decoy {
+    use encoder::{ encode_decoy, Decoy };
+    use room::Room;
+
+    #[test]
+    fn it_generates_invalid_rooms() {
+        for decoy in &[Decoy::Swapped, Decoy::Shifted] {
+            for name in &["very encrypted name", "aaa", "a b c d e f g h", "1", "9", "z", "1 2 3"] {
+                let room = Room::from_line(&encode_decoy(name, 42, *decoy).unwrap());
+                assert!(!room.is_valid(), "{:?} decoy of {} is valid", decoy, name);
+                assert_eq!(room.decipher(&room.caesar()).trim_end(), *name);
+            }
+        }
+    }
+}
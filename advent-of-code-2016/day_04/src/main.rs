@@ -31,108 +31,165 @@ What is the sector ID of the room where North Pole objects are stored?
 
 extern crate regex;
 #[macro_use] extern crate lazy_static;
-use regex::Regex;
+
+mod cipher;
+mod shift_cipher;
+mod vigenere;
+mod affine;
+mod room;
+mod encoder;
+mod cracker;
+mod search;
+mod repair;
+
+use room::Room;
+use encoder::Decoy;
+use cracker::LetterFrequencies;
+use cipher::CipherKind;
+use search::{ Filter, Format };
 use std::io::prelude::*;
 use std::fs::File;
-
-#[derive(Debug)]
-struct Room {
-    name : String,
-    sector_id : isize,
-    checksum: String,
+use std::env;
+use std::process;
+
+// `encode <sector-id> <name..>` prints a valid room line for the name,
+// `decoy <swapped|shifted> <sector-id> <name..>` prints one with a broken checksum
+fn run_encoder(args : &[String], decoy : Option<Result<Decoy, String>>) {
+    let usage = "usage: day_4 encode <sector-id> <name..> | decoy <swapped|shifted> <sector-id> <name..>";
+    let sector_id : Option<isize> = args.first().and_then(|id| id.parse().ok());
+    let name = args.iter().skip(1).cloned().collect::<Vec<String>>().join(" ");
+
+    let line = match (sector_id, decoy) {
+        (Some(sector_id), None) => encoder::encode(&name, sector_id),
+        (Some(sector_id), Some(Ok(decoy))) => encoder::encode_decoy(&name, sector_id, decoy),
+        (_, Some(Err(err))) => Err(format!("{}\n{}", err, usage)),
+        (None, _) => Err(usage.to_string()),
+    };
+
+    match line {
+        Ok(line) => println!("{}", line),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
 }
 
-impl Room {
-    fn from_line(line : &str) -> Room {
+// `crack <encrypted-name> [word-list]` ranks the likely shifts of a name
+// without knowing its sector id
+fn run_cracker(args : &[String]) {
+    let name = match args.first() {
+        Some(name) => name,
+        None => {
+            eprintln!("usage: day_4 crack <encrypted-name> [word-list]");
+            process::exit(1);
+        }
+    };
 
-        lazy_static! {
-            // compile regex only once
-            static ref REGEX : Regex = Regex::new(r"^((([a-zA-Z0-9]+\-)+)([0-9]+)\[(.+)\])$").unwrap();        
+    let frequencies = match args.get(1) {
+        Some(path) => {
+            let words = read_word_list(path);
+            LetterFrequencies::from_words(words.split_whitespace())
         }
+        None => LetterFrequencies::english(),
+    };
 
-        // find matches in line
-        let cap = REGEX.captures(line).unwrap();
+    for candidate in cracker::crack(name, &frequencies).iter().take(5) {
+        println!("{:>2} {:>8.2} {}", candidate.shift, candidate.score, candidate.name);
+    }
+}
 
-        // grab the parts we need
-        let name : String     = cap.get(2).unwrap().as_str().to_string();
-        let sector_id : isize = cap.get(4).unwrap().as_str().parse().unwrap();
-        let checksum : String = cap.get(5).unwrap().as_str().to_string();
+// `search [--pattern <regex>] [--contains <text>] [--words <word-list>] [--format plain|json]`
+// prints the sector id and name of every real room matching all filters
+fn run_search(args : &[String]) {
+    let mut filters = Vec::new();
 
-        // and create a Room struct off of it
-        Room { name: name, sector_id: sector_id, checksum: checksum }
+    if let Some(pattern) = option_value(args, "--pattern") {
+        filters.push(Filter::pattern(pattern).unwrap_or_else(|err| exit_with(&err)));
     }
 
-    // does checksum match input?
-    fn is_valid(&self) -> bool {
-        // remove the dashes
-        let undashed_name : String = self.name.replace('-', "");
-
-        // grab the chars as an array
-        let mut chars_as_vec : Vec<char> = undashed_name.chars().collect();
-
-        // sort the array alphabetically
-        chars_as_vec.sort();
-
-        // dedupe the array
-        chars_as_vec.dedup();
-
-        // create tuples with the character and numer of occurances in original string
-        let mut char_and_count : Vec<(char, usize)> = chars_as_vec.iter()
-            .map(|c| { (*c, undashed_name.matches(*c).count()) })
-            .collect();
-        
-        // ordering.. magic :)
-        char_and_count.sort_by(|a, b| {
-            // if a.1 (number of occurances) equal..
-            if a.1 == b.1 {
-                // matching; compare using the char alphabetically
-                return b.0.cmp(&a.0)
-            }
-            // not matching; how do they compare?
-            a.1.cmp(&b.1)
-        });
-
-        // since the order is backwards, lets reverse
-        char_and_count.reverse();
-
-        // truncate to first five chars
-        char_and_count.truncate(5);
-
-        // grab the chars and concatenate as string
-        let checksum_as_vec : Vec<char> = char_and_count.iter().map(|c| c.0).collect();
-        let checksum : String = checksum_as_vec.clone().into_iter().collect();
-
-        // a room is valid once the two checksums match
-        return checksum == self.checksum
+    if let Some(needle) = option_value(args, "--contains") {
+        filters.push(Filter::Substring(needle.clone()));
     }
 
-    fn decipher(&self) -> String {
-        let undashed_name = self.name.replace('-', " ");
-        rot(undashed_name, self.sector_id as usize)
+    if let Some(path) = option_value(args, "--words") {
+        filters.push(Filter::dictionary(read_word_list(path).split_whitespace()));
     }
+
+    let format : Format = option_value(args, "--format")
+        .map(|f| f.parse().unwrap_or_else(|err : String| exit_with(&err)))
+        .unwrap_or(Format::Plain);
+
+    let rooms = read_rooms();
+    let found = search::search(&rooms, &cipher_kind(args), &filters);
+    print!("{}", search::format(&found, format));
 }
 
-// basic ceasar cipher implementation
-fn rot(input : String, rotations : usize) -> String {
-    let alphabet  = "abcdefghijklmnopqrstuvwxyz".to_string();
+// `diagnose [<room>]` explains why the given room, or every decoy in the
+// input, has an invalid checksum
+fn run_diagnose(args : &[String]) {
+    let rooms = match args.first() {
+        Some(line) => vec![Room::parse(line).unwrap_or_else(|err| exit_with(&err))],
+        None => read_rooms().into_iter().filter(|r| !r.is_valid()).collect(),
+    };
 
-    input.chars()
-        .map(|c| {
-            if c == ' ' { return c; }
-            let new_index = alphabet.find(c).unwrap() + rotations;
-            alphabet.chars().cycle().nth(new_index).unwrap()
-        })
-        .collect()
+    for room in rooms {
+        println!("{}{}[{}]: {}", room.name, room.sector_id, room.checksum, room.diagnose());
+    }
+}
 
-        //  ^ have you seen this cycle thing? 😯
+// `--cipher caesar|vigenere|affine:<a>,<b>` picks how names are deciphered
+fn cipher_kind(args : &[String]) -> CipherKind {
+    match option_value(args, "--cipher") {
+        Some(kind) => kind.parse().unwrap_or_else(|err : String| exit_with(&err)),
+        None => CipherKind::Caesar,
+    }
 }
 
-fn main() {
+// value following `name` in the arguments, exits if the value is missing
+fn option_value<'a>(args : &'a [String], name : &str) -> Option<&'a String> {
+    args.iter().position(|a| a == name).map(|idx| {
+        args.get(idx + 1).unwrap_or_else(|| exit_with(&format!("missing value for {}", name)))
+    })
+}
+
+fn read_word_list(path : &str) -> String {
+    let mut words = String::new();
+    if let Err(err) = File::open(path).and_then(|mut f| f.read_to_string(&mut words)) {
+        exit_with(&format!("could not read word list {}: {}", path, err));
+    }
+    words
+}
+
+fn read_rooms() -> Vec<Room> {
     let mut f = File::open("input.txt").unwrap();
     let mut input = String::new();
     f.read_to_string(&mut input).ok();
 
-    let rooms : Vec<Room> = input.lines().map(|line| {Room::from_line(line)}).collect();
+    input.lines().map(|line| {Room::from_line(line)}).collect()
+}
+
+fn exit_with(message : &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let args : Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|a| a.as_str()) {
+        Some("encode") => return run_encoder(&args[1..], None),
+        Some("crack") => return run_cracker(&args[1..]),
+        Some("search") => return run_search(&args[1..]),
+        Some("diagnose") => return run_diagnose(&args[1..]),
+        Some("decoy") => {
+            let decoy = args.get(1).map(|d| d.parse()).unwrap_or(Ok(Decoy::Swapped));
+            return run_encoder(args.get(2..).unwrap_or(&[]), Some(decoy));
+        }
+        _ => {}
+    }
+
+    let cipher_kind = cipher_kind(&args);
+    let rooms = read_rooms();
 
     // grab the valid rooms
     let valid_rooms : Vec<&Room> = rooms.iter()
@@ -144,7 +201,7 @@ fn main() {
 
     // for part B we're looking for a room where north pole objects are stored
     let deciphered_names : Vec<(isize, String)> = valid_rooms.iter()
-        .map(|r| (r.sector_id, r.decipher() ) )
+        .map(|r| (r.sector_id, r.decipher(&*cipher_kind.cipher_for(r)) ) )
         .collect();
 
     // print what can be found where 💪
@@ -156,14 +213,26 @@ fn main() {
     println!("sum: {}", sum);
 }
 
+#[cfg(test)]
+use shift_cipher::ShiftCipher;
+#[cfg(test)]
+use cipher::Cipher;
+
 #[test]
 fn test_rot() {
-    let res = rot("mec".to_string(), 10);
+    let res = ShiftCipher::new(10).encrypt("mec");
     assert_eq!(res, "wom".to_string())
 }
 
 #[test]
 fn test_rot_for_realsies(){
-    let res = rot("qzmt zixmtkozy ivhz".to_string(), 343);
+    let res = ShiftCipher::new(343).encrypt("qzmt zixmtkozy ivhz");
     assert_eq!(res, "very encrypted name".to_string());
 }
+
+#[test]
+fn test_rot_through_room(){
+    let room = Room::from_line("qzmt-zixmtkozy-ivhz-343[zimth]");
+    let res = room.decipher(&room.caesar());
+    assert_eq!(res, "very encrypted name ".to_string());
+}
@@ -0,0 +1,366 @@
+use room::{ checksum, Room };
+use std::fmt;
+
+// how a single checksum position compares to the expected checksum
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+    Match(char),
+    Mismatch { expected: char, supplied: char },
+    // the supplied checksum is too short
+    Missing(char),
+    // the supplied checksum is too long
+    Unexpected(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    // swapping the letters at these positions fixes the checksum
+    OneSwapAway(usize, usize),
+    // the supplied letter occurs as often as the expected one, so the tie
+    // wasn't broken alphabetically
+    TiedCount { expected: char, supplied: char, count: usize },
+    // the checksum doesn't follow from the name, so the name was probably mangled
+    LikelyCorrupted,
+}
+
+// a single character change to the name. Positions don't influence the
+// checksum, so neither do these.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameEdit {
+    Insert(char),
+    Delete(char),
+    Replace { from: char, to: char },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    pub expected: String,
+    pub supplied: String,
+    pub diff: Vec<Position>,
+    pub problems: Vec<Problem>,
+    // the fewest edits that make the supplied checksum correct, None when
+    // no name could have it
+    pub edits: Option<Vec<NameEdit>>,
+}
+
+impl Diagnosis {
+    pub fn is_valid(&self) -> bool {
+        self.expected == self.supplied
+    }
+}
+
+pub fn diagnose(room : &Room) -> Diagnosis {
+    let expected = checksum(&room.name);
+    let supplied = room.checksum.clone();
+
+    if expected == supplied {
+        return Diagnosis {
+            diff: expected.chars().map(Position::Match).collect(),
+            expected, supplied,
+            problems: Vec::new(),
+            edits: Some(Vec::new()),
+        };
+    }
+
+    let diff = diff(&expected, &supplied);
+    let problems = problems(&room.name, &expected, &supplied, &diff);
+    let edits = edits(&room.name, &supplied);
+
+    Diagnosis { expected, supplied, diff, problems, edits }
+}
+
+fn diff(expected : &str, supplied : &str) -> Vec<Position> {
+    let expected : Vec<char> = expected.chars().collect();
+    let supplied : Vec<char> = supplied.chars().collect();
+
+    (0..expected.len().max(supplied.len()))
+        .map(|idx| match (expected.get(idx), supplied.get(idx)) {
+            (Some(&e), Some(&s)) if e == s => Position::Match(e),
+            (Some(&expected), Some(&supplied)) => Position::Mismatch { expected, supplied },
+            (Some(&e), None) => Position::Missing(e),
+            (None, Some(&s)) => Position::Unexpected(s),
+            (None, None) => unreachable!(),
+        })
+        .collect()
+}
+
+fn problems(name : &str, expected : &str, supplied : &str, diff : &[Position]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    // exactly two mismatches that mirror each other
+    let mismatches : Vec<usize> = diff.iter().enumerate()
+        .filter(|&(_, p)| !matches!(*p, Position::Match(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if expected.len() == supplied.len() && mismatches.len() == 2 {
+        let mut swapped : Vec<char> = supplied.chars().collect();
+        swapped.swap(mismatches[0], mismatches[1]);
+        if swapped.into_iter().collect::<String>() == expected {
+            problems.push(Problem::OneSwapAway(mismatches[0], mismatches[1]));
+        }
+    }
+
+    // letters that were picked over an equally common one
+    for position in diff {
+        if let Position::Mismatch { expected, supplied } = *position {
+            let count = occurrences(name, expected);
+            if count > 0 && count == occurrences(name, supplied) {
+                problems.push(Problem::TiedCount { expected, supplied, count });
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        problems.push(Problem::LikelyCorrupted);
+    }
+
+    problems
+}
+
+// The fewest edits to the name that make the supplied checksum correct, or
+// None when no name has that checksum. Only the letter counts matter: the
+// supplied letters need counts that sort in their order, and every other
+// letter has to end up behind the last of them. Inserts and deletes are one
+// edit each, a replace does one of both in a single edit.
+fn edits(name : &str, supplied : &str) -> Option<Vec<NameEdit>> {
+    let wanted : Vec<char> = supplied.chars().collect();
+    let mut distinct = wanted.clone();
+    distinct.sort();
+    distinct.dedup();
+    if wanted.is_empty() || wanted.len() > 5 || distinct.len() != wanted.len()
+        || !wanted.iter().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    // every letter that could play a part, in checksum tie order
+    let mut letters : Vec<char> = "abcdefghijklmnopqrstuvwxyz".chars()
+        .chain(name.chars().filter(|c| *c != '-'))
+        .chain(wanted.iter().cloned())
+        .collect();
+    letters.sort();
+    letters.dedup();
+
+    let counts : Vec<usize> = letters.iter().map(|&l| occurrences(name, l)).collect();
+    let positions : Vec<usize> = wanted.iter().map(|w| letters.iter().position(|l| l == w).unwrap()).collect();
+    let last = *positions.last().unwrap();
+
+    let mut best : Option<((usize, usize), Vec<usize>)> = None;
+    let highest = counts.iter().cloned().max().unwrap_or(0) + wanted.len();
+
+    for last_count in 1..highest + 1 {
+        // the others drop to the last checksum letter's count, or just below
+        // it if they'd win the tie. Without five checksum letters they're gone.
+        let mut target : Vec<usize> = counts.iter().enumerate()
+            .map(|(idx, &count)| match wanted.len() {
+                5 if idx > last => count.min(last_count),
+                5 => count.min(last_count - 1),
+                _ => 0,
+            })
+            .collect();
+
+        let removed = target.iter().zip(&counts).enumerate()
+            .filter(|&(idx, _)| !positions.contains(&idx))
+            .map(|(_, (t, c))| c - t)
+            .sum();
+
+        let mut search = Search { positions: &positions, counts: &counts, last_count, best: &mut best };
+        search.assign(0, highest, &mut target, 0, removed);
+    }
+
+    best.map(|(_, target)| {
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for (idx, &letter) in letters.iter().enumerate() {
+            for _ in counts[idx]..target[idx] {
+                added.push(letter);
+            }
+            for _ in target[idx]..counts[idx] {
+                removed.push(letter);
+            }
+        }
+
+        let replaces = added.len().min(removed.len());
+        removed.iter().zip(added.iter()).map(|(&from, &to)| NameEdit::Replace { from, to })
+            .chain(added[replaces..].iter().map(|&c| NameEdit::Insert(c)))
+            .chain(removed[replaces..].iter().map(|&c| NameEdit::Delete(c)))
+            .collect()
+    })
+}
+
+// Tries counts for the checksum letters, keeping the target with the fewest
+// edits. Counts go down along the checksum, strictly where the letters aren't
+// in alphabetical order, and the last letter gets `last_count`.
+struct Search<'a> {
+    positions: &'a [usize],
+    counts: &'a [usize],
+    last_count: usize,
+    best: &'a mut Option<((usize, usize), Vec<usize>)>,
+}
+
+impl<'a> Search<'a> {
+    fn strict(&self, idx : usize) -> usize {
+        (self.positions[idx] > self.positions[idx + 1]) as usize
+    }
+
+    // lowest count the letter at `idx` can have, leaving room for the ones after it
+    fn lower(&self, idx : usize) -> usize {
+        self.last_count + (idx..self.positions.len() - 1).map(|i| self.strict(i)).sum::<usize>()
+    }
+
+    // `added` and `removed` count the edits so far, `upper` is the highest count allowed at `idx`
+    fn assign(&mut self, idx : usize, upper : usize, target : &mut Vec<usize>, added : usize, removed : usize) {
+        let last = self.positions.len() - 1;
+
+        if idx > last {
+            *self.best = Some(((added.max(removed), added + removed), target.clone()));
+            return;
+        }
+
+        let upper = if idx == last { upper.min(self.last_count) } else { upper };
+        let lower = self.lower(idx);
+
+        // the letters left need at least these edits. Edits only pile up, so
+        // a branch that can't beat the best target yet is done.
+        let (mut least_added, mut least_removed) = (added, removed);
+        for i in idx..last + 1 {
+            let count = self.counts[self.positions[i]];
+            least_added += self.lower(i).saturating_sub(count);
+            least_removed += count.saturating_sub(if i == last { self.last_count } else { upper });
+        }
+        let least = (least_added.max(least_removed), least_added + least_removed);
+        if lower > upper || self.best.as_ref().is_some_and(|b| least >= b.0) {
+            return;
+        }
+
+        let letter = self.positions[idx];
+        let count = self.counts[letter];
+
+        // closest to the current count first, so good targets turn up early
+        let mut options : Vec<usize> = (lower..upper + 1).collect();
+        options.sort_by_key(|&new| (new as isize - count as isize).abs());
+
+        for new in options {
+            target[letter] = new;
+            let next = if idx == last { 0 } else { new - self.strict(idx) };
+            self.assign(idx + 1, next, target, added + new.saturating_sub(count), removed + count.saturating_sub(new));
+        }
+    }
+}
+
+fn occurrences(name : &str, c : char) -> usize {
+    name.chars().filter(|n| *n == c).count()
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "checksum {} is valid", self.supplied);
+        }
+
+        writeln!(f, "expected checksum {}, got {}", self.expected, self.supplied)?;
+
+        for problem in &self.problems {
+            match *problem {
+                Problem::OneSwapAway(a, b) => writeln!(f, "  positions {} and {} are swapped", a, b)?,
+                Problem::TiedCount { expected, supplied, count } =>
+                    writeln!(f, "  '{}' and '{}' both occur {} times, ties go alphabetically", expected, supplied, count)?,
+                Problem::LikelyCorrupted => writeln!(f, "  name is likely corrupted")?,
+            }
+        }
+
+        if let Some(ref edits) = self.edits {
+            let edits : Vec<String> = edits.iter()
+                .map(|e| match *e {
+                    NameEdit::Insert(c) => format!("+{}", c),
+                    NameEdit::Delete(c) => format!("-{}", c),
+                    NameEdit::Replace { from, to } => format!("{}>{}", from, to),
+                })
+                .collect();
+            write!(f, "  name edits that fix it: {}", edits.join(" "))
+        } else {
+            write!(f, "  no name has this checksum")
+        }
+    }
+}
+
+#[cfg(test)]
+mod diagnose {
+    use repair::{ diagnose, NameEdit, Position, Problem };
+    use room::{ checksum, Room };
+
+    #[test]
+    fn it_accepts_valid_rooms() {
+        let diagnosis = diagnose(&Room::from_line("aaaaa-bbb-z-y-x-123[abxyz]"));
+        assert!(diagnosis.is_valid());
+        assert!(diagnosis.problems.is_empty());
+        assert_eq!(diagnosis.edits, Some(vec![]));
+    }
+
+    #[test]
+    fn it_detects_swapped_letters() {
+        let diagnosis = diagnose(&Room::from_line("aaaaa-bbb-z-y-x-123[baxyz]"));
+
+        assert_eq!(diagnosis.expected, "abxyz");
+        assert_eq!(diagnosis.diff[0], Position::Mismatch { expected: 'a', supplied: 'b' });
+        assert_eq!(diagnosis.diff[2], Position::Match('x'));
+        assert_eq!(diagnosis.problems, vec![Problem::OneSwapAway(0, 1)]);
+    }
+
+    #[test]
+    fn it_detects_broken_ties() {
+        let diagnosis = diagnose(&Room::from_line("a-b-c-d-e-f-g-h-987[abcdf]"));
+
+        assert_eq!(diagnosis.problems, vec![Problem::TiedCount { expected: 'e', supplied: 'f', count: 1 }]);
+        assert_eq!(diagnosis.edits, Some(vec![NameEdit::Delete('e')]));
+    }
+
+    #[test]
+    fn it_suggests_name_edits_for_corrupted_names() {
+        let diagnosis = diagnose(&Room::from_line("aaaaa-bbb-z-y-q-123[abxyz]"));
+
+        assert_eq!(diagnosis.problems, vec![Problem::LikelyCorrupted]);
+        assert_eq!(diagnosis.edits, Some(vec![NameEdit::Replace { from: 'q', to: 'x' }]));
+    }
+
+    #[test]
+    fn it_reports_length_differences() {
+        let diagnosis = diagnose(&Room::from_line("aaaaa-bbb-z-y-x-123[abx]"));
+        assert_eq!(diagnosis.diff[3], Position::Missing('y'));
+
+        // a three letter checksum only works for names with three letters
+        assert_eq!(diagnosis.edits, Some(vec![NameEdit::Delete('y'), NameEdit::Delete('z')]));
+    }
+
+    #[test]
+    fn it_suggests_multi_letter_repairs() {
+        // a has to drop behind four letters that occur once
+        let room = Room::from_line("aaa-b-c-d-e-f-123[bcdea]");
+        let edits = diagnose(&room).edits.unwrap();
+        assert_eq!(edits, vec![
+            NameEdit::Replace { from: 'a', to: 'b' },
+            NameEdit::Replace { from: 'a', to: 'c' },
+            NameEdit::Insert('d'),
+            NameEdit::Insert('e'),
+        ]);
+
+        let mut name = room.name.clone();
+        for edit in &edits {
+            name = match *edit {
+                NameEdit::Insert(c) => format!("{}{}", name, c),
+                NameEdit::Delete(c) => name.replacen(c, "", 1),
+                NameEdit::Replace { from, to } => name.replacen(from, &to.to_string(), 1),
+            };
+        }
+        assert_eq!(checksum(&name), "bcdea");
+    }
+
+    #[test]
+    fn it_knows_when_no_name_fits() {
+        for line in &["abc-1[aab]", "abc-1[abcdef]", "abc-1[a!]"] {
+            let diagnosis = diagnose(&Room::from_line(line));
+            assert_eq!(diagnosis.edits, None);
+            assert!(diagnosis.to_string().ends_with("no name has this checksum"));
+        }
+    }
+}
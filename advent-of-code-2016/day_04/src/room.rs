@@ -0,0 +1,132 @@
+use regex::Regex;
+use cipher::Cipher;
+use shift_cipher::ShiftCipher;
+use vigenere::Vigenere;
+use repair::{ self, Diagnosis };
+
+#[derive(Debug)]
+pub struct Room {
+    pub name : String,
+    pub sector_id : isize,
+    pub checksum: String,
+}
+
+impl Room {
+    pub fn from_line(line : &str) -> Room {
+        Room::parse(line).unwrap()
+    }
+
+    // like `from_line`, but tells what is wrong with the line instead of panicking
+    pub fn parse(line : &str) -> Result<Room, String> {
+
+        lazy_static! {
+            // compile regex only once
+            static ref REGEX : Regex = Regex::new(r"^((([a-zA-Z0-9]+\-)+)([0-9]+)\[(.+)\])$").unwrap();        
+        }
+
+        // find matches in line
+        let cap = REGEX.captures(line)
+            .ok_or_else(|| format!("'{}' is not a room, expected <name>-<sector id>[<checksum>]", line))?;
+
+        // grab the parts we need
+        let name : String     = cap.get(2).unwrap().as_str().to_string();
+        let sector_id : isize = cap.get(4).unwrap().as_str().parse()
+            .map_err(|err| format!("invalid sector id in '{}': {}", line, err))?;
+        let checksum : String = cap.get(5).unwrap().as_str().to_string();
+
+        // and create a Room struct off of it
+        Ok(Room { name, sector_id, checksum })
+    }
+
+    // does checksum match input?
+    pub fn is_valid(&self) -> bool {
+        // a room is valid once the two checksums match
+        checksum(&self.name) == self.checksum
+    }
+
+    // explains why the checksum doesn't match, and how to fix it
+    pub fn diagnose(&self) -> Diagnosis {
+        repair::diagnose(self)
+    }
+
+    pub fn decipher<C: Cipher + ?Sized>(&self, cipher : &C) -> String {
+        let undashed_name = self.name.replace('-', " ");
+        cipher.decrypt(&undashed_name)
+    }
+
+    // names are encrypted by rotating backwards by the sector id,
+    // decrypting rotates them forward again
+    pub fn caesar(&self) -> ShiftCipher {
+        ShiftCipher::new(-self.sector_id)
+    }
+
+    // keyed feeds use the checksum as vigenère key
+    pub fn vigenere(&self) -> Vigenere {
+        Vigenere::new(&self.checksum)
+    }
+}
+
+// five most common characters in the name, ties broken alphabetically
+pub fn checksum(name : &str) -> String {
+    // remove the dashes
+    let undashed_name : String = name.replace('-', "");
+
+    // grab the chars as an array
+    let mut chars_as_vec : Vec<char> = undashed_name.chars().collect();
+
+    // sort the array alphabetically
+    chars_as_vec.sort();
+
+    // dedupe the array
+    chars_as_vec.dedup();
+
+    // create tuples with the character and numer of occurances in original string
+    let mut char_and_count : Vec<(char, usize)> = chars_as_vec.iter()
+        .map(|c| { (*c, undashed_name.matches(*c).count()) })
+        .collect();
+    
+    // ordering.. magic :)
+    char_and_count.sort_by(|a, b| {
+        // if a.1 (number of occurances) equal..
+        if a.1 == b.1 {
+            // matching; compare using the char alphabetically
+            return b.0.cmp(&a.0)
+        }
+        // not matching; how do they compare?
+        a.1.cmp(&b.1)
+    });
+
+    // since the order is backwards, lets reverse
+    char_and_count.reverse();
+
+    // truncate to first five chars
+    char_and_count.truncate(5);
+
+    // grab the chars and concatenate as string
+    let checksum_as_vec : Vec<char> = char_and_count.iter().map(|c| c.0).collect();
+    checksum_as_vec.into_iter().collect()
+}
+
+#[cfg(test)]
+mod is_valid {
+    use room::Room;
+
+    #[test]
+    fn it_accepts_real_rooms() {
+        assert!(Room::from_line("aaaaa-bbb-z-y-x-123[abxyz]").is_valid());
+        assert!(Room::from_line("a-b-c-d-e-f-g-h-987[abcde]").is_valid());
+        assert!(Room::from_line("not-a-real-room-404[oarel]").is_valid());
+    }
+
+    #[test]
+    fn it_reports_malformed_rooms() {
+        assert!(Room::parse("not-a-room").is_err());
+        assert!(Room::parse("room-99999999999999999999999[abcde]").is_err());
+        assert_eq!(Room::parse("a-b-1[ab]").unwrap().sector_id, 1);
+    }
+
+    #[test]
+    fn it_rejects_decoys() {
+        assert!(!Room::from_line("totally-real-room-200[decoy]").is_valid());
+    }
+}
@@ -0,0 +1,172 @@
+use regex::Regex;
+use room::Room;
+use cipher::CipherKind;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+// keeps deciphered room names that match
+pub enum Filter {
+    Pattern(Regex),
+    Substring(String),
+    // every word in the name should be in the dictionary
+    Dictionary(HashSet<String>),
+}
+
+impl Filter {
+    pub fn pattern(pattern : &str) -> Result<Filter, String> {
+        Regex::new(pattern)
+            .map(Filter::Pattern)
+            .map_err(|err| format!("invalid pattern '{}': {}", pattern, err))
+    }
+
+    pub fn dictionary<'a, I: IntoIterator<Item = &'a str>>(words : I) -> Filter {
+        Filter::Dictionary(words.into_iter().map(|w| w.to_lowercase()).collect())
+    }
+
+    pub fn matches(&self, name : &str) -> bool {
+        match *self {
+            Filter::Pattern(ref regex) => regex.is_match(name),
+            Filter::Substring(ref needle) => name.contains(needle.as_str()),
+            Filter::Dictionary(ref words) => {
+                let mut name_words = name.split_whitespace().peekable();
+                name_words.peek().is_some() && name_words.all(|w| words.contains(&w.to_lowercase()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Found {
+    pub sector_id: isize,
+    pub name: String,
+}
+
+// deciphers all real rooms and keeps the ones matching every filter
+pub fn search(rooms : &[Room], cipher_kind : &CipherKind, filters : &[Filter]) -> Vec<Found> {
+    rooms.iter()
+        .filter(|r| r.is_valid())
+        .map(|r| Found {
+            sector_id: r.sector_id,
+            name: r.decipher(&*cipher_kind.cipher_for(r)).trim().to_string(),
+        })
+        .filter(|found| filters.iter().all(|f| f.matches(&found.name)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Plain,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{}', expected plain or json", s)),
+        }
+    }
+}
+
+// plain prints `<sector-id> <name>` per line, json an array of objects
+pub fn format(found : &[Found], format : Format) -> String {
+    match format {
+        Format::Plain => found.iter()
+            .map(|f| format!("{} {}\n", f.sector_id, f.name))
+            .collect(),
+        Format::Json => {
+            let objects : Vec<String> = found.iter()
+                .map(|f| format!("{{\"sector_id\":{},\"name\":\"{}\"}}", f.sector_id, escape_json(&f.name)))
+                .collect();
+            format!("[{}]\n", objects.join(","))
+        }
+    }
+}
+
+fn escape_json(input : &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod search_rooms {
+    use search::{ search, Filter, Found };
+    use cipher::CipherKind;
+    use room::Room;
+
+    fn rooms() -> Vec<Room> {
+        vec![
+            Room::from_line("qzmt-zixmtkozy-ivhz-343[zimth]"),
+            Room::from_line("lmprfnmjc-mzhcar-qrmpyec-548[mcrpa]"),
+            Room::from_line("lmprfnmjc-mzhcar-qrmpyec-548[cmrpa]"),
+        ]
+    }
+
+    #[test]
+    fn it_filters_by_pattern() {
+        let filters = vec![Filter::pattern("^north.*storage$").unwrap()];
+        let found = search(&rooms(), &CipherKind::Caesar, &filters);
+        assert_eq!(found, vec![Found { sector_id: 548, name: "northpole object storage".to_string() }]);
+    }
+
+    #[test]
+    fn it_filters_by_substring() {
+        let filters = vec![Filter::Substring("crypt".to_string())];
+        let found = search(&rooms(), &CipherKind::Caesar, &filters);
+        assert_eq!(found, vec![Found { sector_id: 343, name: "very encrypted name".to_string() }]);
+    }
+
+    #[test]
+    fn it_filters_by_dictionary() {
+        let filters = vec![Filter::dictionary(vec!["Northpole", "object", "storage", "very", "name"])];
+        let found = search(&rooms(), &CipherKind::Caesar, &filters);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].sector_id, 548);
+    }
+
+    #[test]
+    fn it_skips_decoys_and_combines_filters() {
+        assert_eq!(search(&rooms(), &CipherKind::Caesar, &[]).len(), 2);
+
+        let filters = vec![Filter::Substring("e".to_string()), Filter::pattern("^very").unwrap()];
+        assert_eq!(search(&rooms(), &CipherKind::Caesar, &filters).len(), 1);
+    }
+
+    #[test]
+    fn it_rejects_invalid_patterns() {
+        assert!(Filter::pattern("(unclosed").is_err());
+    }
+}
+
+#[cfg(test)]
+mod format {
+    use search::{ format, Format, Found };
+
+    #[test]
+    fn it_formats_plain_text_and_json() {
+        let found = vec![
+            Found { sector_id: 548, name: "northpole object storage".to_string() },
+            Found { sector_id: 1, name: "say \"hi\"".to_string() },
+        ];
+
+        assert_eq!(format(&found, Format::Plain), "548 northpole object storage\n1 say \"hi\"\n");
+        assert_eq!(
+            format(&found, Format::Json),
+            "[{\"sector_id\":548,\"name\":\"northpole object storage\"},{\"sector_id\":1,\"name\":\"say \\\"hi\\\"\"}]\n"
+        );
+        assert_eq!(format(&[], Format::Json), "[]\n");
+    }
+}
@@ -0,0 +1,133 @@
+use cipher::Cipher;
+use std::collections::HashMap;
+
+pub const LOWERCASE_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+// shift cipher over an arbitrary alphabet. Characters outside the alphabet
+// (digits, punctuation, spaces..) are passed through untouched, uppercase
+// letters are rotated as their lowercase counterpart and uppercased again.
+#[derive(Debug, Clone)]
+pub struct ShiftCipher {
+    alphabet: Vec<char>,
+    positions: HashMap<char, usize>,
+    shift: usize,
+}
+
+impl ShiftCipher {
+    // shift cipher over the lowercase latin alphabet
+    pub fn new(shift: isize) -> ShiftCipher {
+        ShiftCipher::with_alphabet(LOWERCASE_ALPHABET, shift)
+            .expect("latin alphabet should be valid")
+    }
+
+    // shift cipher over a custom alphabet, every character may only occur once
+    pub fn with_alphabet(alphabet: &str, shift: isize) -> Result<ShiftCipher, String> {
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        if alphabet.is_empty() {
+            return Err("alphabet should not be empty".to_string());
+        }
+
+        // remember the position of every character so lookups are O(1)
+        let mut positions = HashMap::with_capacity(alphabet.len());
+        for (idx, c) in alphabet.iter().enumerate() {
+            if positions.insert(*c, idx).is_some() {
+                return Err(format!("alphabet contains '{}' more than once", c));
+            }
+        }
+
+        // normalize (possibly negative) shift to a forward rotation
+        let shift = shift.rem_euclid(alphabet.len() as isize) as usize;
+
+        Ok(ShiftCipher { alphabet, positions, shift })
+    }
+
+    fn rotate(&self, c: char, by: usize) -> char {
+        if let Some(idx) = self.positions.get(&c) {
+            return self.alphabet[(idx + by) % self.alphabet.len()];
+        }
+
+        // not in the alphabet, maybe its lowercase version is?
+        if c.is_uppercase() {
+            let mut lower = c.to_lowercase();
+            if let (Some(l), None) = (lower.next(), lower.next()) {
+                if let Some(idx) = self.positions.get(&l) {
+                    let rotated = self.alphabet[(idx + by) % self.alphabet.len()];
+                    return rotated.to_uppercase().next().unwrap_or(rotated);
+                }
+            }
+        }
+
+        // pass through anything else
+        c
+    }
+}
+
+impl Cipher for ShiftCipher {
+    // rotate every character forward by `shift`
+    fn encrypt(&self, input: &str) -> String {
+        input.chars().map(|c| self.rotate(c, self.shift)).collect()
+    }
+
+    // rotate every character backward by `shift`
+    fn decrypt(&self, input: &str) -> String {
+        let backwards = (self.alphabet.len() - self.shift) % self.alphabet.len();
+        input.chars().map(|c| self.rotate(c, backwards)).collect()
+    }
+}
+
+#[cfg(test)]
+mod encrypt {
+    use shift_cipher::ShiftCipher;
+    use cipher::Cipher;
+
+    #[test]
+    fn it_rotates_lowercase_letters() {
+        assert_eq!(ShiftCipher::new(10).encrypt("mec"), "wom");
+        assert_eq!(ShiftCipher::new(343).encrypt("qzmt zixmtkozy ivhz"), "very encrypted name");
+    }
+
+    #[test]
+    fn it_preserves_case() {
+        assert_eq!(ShiftCipher::new(1).encrypt("Zebra"), "Afcsb");
+    }
+
+    #[test]
+    fn it_passes_through_digits_and_punctuation() {
+        assert_eq!(ShiftCipher::new(3).encrypt("abc-123, xyz!"), "def-123, abc!");
+    }
+
+    #[test]
+    fn it_accepts_negative_shifts() {
+        assert_eq!(ShiftCipher::new(-1).encrypt("abc"), "zab");
+    }
+
+    #[test]
+    fn it_supports_custom_alphabets() {
+        let cipher = ShiftCipher::with_alphabet("0123456789", 3).unwrap();
+        assert_eq!(cipher.encrypt("0789 abc"), "3012 abc");
+    }
+
+    #[test]
+    fn it_rejects_invalid_alphabets() {
+        assert!(ShiftCipher::with_alphabet("", 1).is_err());
+        assert!(ShiftCipher::with_alphabet("abca", 1).is_err());
+    }
+}
+
+#[cfg(test)]
+mod decrypt {
+    use shift_cipher::ShiftCipher;
+    use cipher::Cipher;
+
+    #[test]
+    fn it_reverses_encrypt() {
+        let cipher = ShiftCipher::new(343);
+        let input = "Very encrypted name, sector 343!";
+        assert_eq!(cipher.decrypt(&cipher.encrypt(input)), input);
+    }
+
+    #[test]
+    fn it_handles_zero_shift() {
+        assert_eq!(ShiftCipher::new(26).decrypt("abc"), "abc");
+    }
+}
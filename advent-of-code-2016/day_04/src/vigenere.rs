@@ -0,0 +1,74 @@
+use cipher::Cipher;
+
+// vigenère cipher over a-z, every letter in the key shifts one letter of the
+// input. Characters outside a-z are passed through and don't use up the key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vigenere {
+    shifts: Vec<usize>,
+}
+
+impl Vigenere {
+    // anything but letters in the key is ignored, an empty key doesn't encrypt at all
+    pub fn new(key: &str) -> Vigenere {
+        let shifts = key.chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .map(|c| (c.to_ascii_lowercase() as u8 - b'a') as usize)
+            .collect();
+
+        Vigenere { shifts }
+    }
+
+    fn rotate(&self, input: &str, forward: bool) -> String {
+        if self.shifts.is_empty() {
+            return input.to_string();
+        }
+
+        let mut key = self.shifts.iter().cycle();
+        input.chars()
+            .map(|c| {
+                if !c.is_ascii_alphabetic() {
+                    return c;
+                }
+
+                let base = if c.is_ascii_uppercase() { b'A' } else { b'a' };
+                let shift = *key.next().unwrap();
+                let shift = if forward { shift } else { 26 - shift };
+                (base + ((c as u8 - base) as usize + shift) as u8 % 26) as char
+            })
+            .collect()
+    }
+}
+
+impl Cipher for Vigenere {
+    fn encrypt(&self, input: &str) -> String {
+        self.rotate(input, true)
+    }
+
+    fn decrypt(&self, input: &str) -> String {
+        self.rotate(input, false)
+    }
+}
+
+#[cfg(test)]
+mod encrypt {
+    use vigenere::Vigenere;
+    use cipher::Cipher;
+
+    #[test]
+    fn it_encrypts_the_classic_example() {
+        let cipher = Vigenere::new("LEMON");
+        assert_eq!(cipher.encrypt("ATTACK AT DAWN"), "LXFOPV EF RNHR");
+    }
+
+    #[test]
+    fn it_roundtrips() {
+        let cipher = Vigenere::new("zimth");
+        let input = "Very encrypted name, sector 343";
+        assert_eq!(cipher.decrypt(&cipher.encrypt(input)), input);
+    }
+
+    #[test]
+    fn it_ignores_empty_keys() {
+        assert_eq!(Vigenere::new("123").encrypt("abc"), "abc");
+    }
+}
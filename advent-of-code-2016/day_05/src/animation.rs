@@ -0,0 +1,142 @@
+use ctrlc;
+use std::io::{ self, Write };
+use std::process;
+use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::thread::{ self, JoinHandle };
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+const FRAME : Duration = Duration::from_millis(50);
+
+// Hacker movie style "decrypting" of the password: a thread redraws the line
+// in place every frame, unknown characters cycle through random hex and known
+// ones stay put. The password itself only changes through `update`, so what is
+// shown locked in is always what the search really found.
+pub struct Animation {
+    state: Arc<Mutex<(String, String)>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Animation {
+    // `partial` is the password so far, with `_` for unknown characters
+    pub fn start(partial : &str) -> Animation {
+        let state = Arc::new(Mutex::new((partial.to_string(), String::new())));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // Ctrl-C skips every destructor, so the cursor has to come back from here.
+        // Setting the handler only fails when there already is one, which is fine.
+        let _ = ctrlc::set_handler(|| {
+            println!("\x1b[?25h");
+            process::exit(130);
+        });
+
+        let thread = {
+            let state = state.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut rng = Rng::from_time();
+
+                // hide the cursor while the line keeps changing
+                print!("\x1b[?25l");
+                while !stop.load(Ordering::Relaxed) {
+                    let line = {
+                        let state = state.lock().unwrap();
+                        format!("{}  {}", scramble(&state.0, &mut rng), state.1)
+                    };
+                    draw(&line);
+                    thread::sleep(FRAME);
+                }
+            })
+        };
+
+        Animation { state, stop, thread: Some(thread) }
+    }
+
+    // `status` is shown next to the password
+    pub fn update(&self, partial : &str, status : String) {
+        *self.state.lock().unwrap() = (partial.to_string(), status);
+    }
+
+    // stops cycling and leaves the final password on screen
+    pub fn finish(mut self) {
+        self.stop_thread();
+        let state = self.state.lock().unwrap();
+        draw(&format!("{}  {}", state.0, state.1));
+        println!("\x1b[?25h");
+    }
+
+    fn stop_thread(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// a panic halfway shouldn't leave the terminal without a cursor
+impl Drop for Animation {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.stop_thread();
+            println!("\x1b[?25h");
+        }
+    }
+}
+
+fn draw(line : &str) {
+    // back to the start of the line and clear it
+    print!("\r\x1b[2K{}", line);
+    let _ = io::stdout().flush();
+}
+
+// replaces every `_` with a random hex character
+fn scramble(partial : &str, rng : &mut Rng) -> String {
+    const HEX : &[u8; 16] = b"0123456789abcdef";
+
+    partial.chars()
+        .map(|c| if c == '_' { HEX[(rng.next() % 16) as usize] as char } else { c })
+        .collect()
+}
+
+// xorshift, plenty random for some flickering characters
+struct Rng(u64);
+
+impl Rng {
+    fn from_time() -> Rng {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        Rng(u64::from(nanos) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod scramble {
+    use animation::{ scramble, Rng };
+
+    #[test]
+    fn it_keeps_known_characters() {
+        let mut rng = Rng(42);
+        for _ in 0..100 {
+            let frame = scramble("_5__e___", &mut rng);
+            assert_eq!(frame.len(), 8);
+            assert_eq!(&frame[1..2], "5");
+            assert_eq!(&frame[4..5], "e");
+            assert!(frame.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+        }
+    }
+
+    #[test]
+    fn it_cycles_unknown_characters() {
+        let mut rng = Rng(42);
+        let frames : Vec<String> = (0..10).map(|_| scramble("________", &mut rng)).collect();
+        assert!(frames.windows(2).any(|w| w[0] != w[1]));
+        assert_eq!(scramble("05ace8e3", &mut rng), "05ace8e3");
+    }
+}
@@ -0,0 +1,62 @@
+use md5;
+use hasher::{ has_leading_zeroes, to_hex, DoorHasher, LaneHasher };
+use md5_lanes::Backend;
+use std::hint::black_box;
+use std::time::{ Duration, Instant };
+
+// The shape of the original loop on our own md5: format every index into a
+// new string and compare the digest as hex. Measures what formatting and
+// allocating cost, the hashing is the same everywhere.
+fn formatting(door_id : &str, indices : u64) -> usize {
+    (0..indices)
+        .filter(|i| {
+            let input : &str = &format!("{}{}", door_id, i);
+            to_hex(black_box(&md5::digest(input.as_bytes()))).starts_with("00000")
+        })
+        .count()
+}
+
+fn allocation_free(door_id : &str, indices : u64) -> usize {
+    let mut hasher = DoorHasher::<md5::Md5>::new(door_id);
+    (0..indices)
+        .filter(|i| {
+            has_leading_zeroes(black_box(hasher.digest(*i)), 5)
+        })
+        .count()
+}
+
+fn lanes(door_id : &str, indices : u64, backend : Backend) -> usize {
+    let mut hasher = LaneHasher::new(door_id, backend);
+    (0..indices)
+        .filter(|i| {
+            has_leading_zeroes(black_box(hasher.digest(*i)), 5)
+        })
+        .count()
+}
+
+fn measure<F: Fn() -> usize>(name : &str, indices : u64, f : F) -> Duration {
+    let start = Instant::now();
+    let hits = f();
+    let elapsed = start.elapsed();
+
+    let rate = indices as f64 / elapsed.as_secs_f64();
+    println!("{:<16} {:>8.0?} {:>12.0} hashes/s ({} hits)", name, elapsed, rate, hits);
+    elapsed
+}
+
+// hashes the first `indices` indices every way and prints the speedups
+pub fn run(door_id : &str, indices : u64) {
+    let slow = measure("format + alloc", indices, || formatting(door_id, indices));
+    let fast = measure("allocation free", indices, || allocation_free(door_id, indices));
+    println!("speedup: {:.2}x", slow.as_secs_f64() / fast.as_secs_f64());
+
+    for &backend in &[Backend::Portable, Backend::Sse2, Backend::Avx2] {
+        if !backend.is_supported() {
+            continue;
+        }
+
+        let name = format!("{} x{}", backend, backend.lanes());
+        let lanes = measure(&name, indices, || lanes(door_id, indices, backend));
+        println!("speedup over allocation free: {:.2}x", fast.as_secs_f64() / lanes.as_secs_f64());
+    }
+}
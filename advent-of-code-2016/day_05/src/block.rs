@@ -0,0 +1,74 @@
+// Buffers input into 64 byte blocks and pads the last one, the part that
+// MD5, SHA-1 and SHA-256 have in common. Only the byte order of the length differs.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockBuffer {
+    // bytes that don't fill a whole block yet
+    buffer: [u8; 64],
+    buffered: usize,
+    // total number of bytes absorbed
+    length: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl BlockBuffer {
+    pub fn new() -> BlockBuffer {
+        BlockBuffer { buffer: [0; 64], buffered: 0, length: 0 }
+    }
+
+    // calls `compress` for every block that fills up
+    pub fn update<F: FnMut(&[u8])>(&mut self, mut input : &[u8], mut compress : F) {
+        self.length = self.length.wrapping_add(input.len() as u64);
+
+        // top up a partially filled buffer first
+        if self.buffered > 0 {
+            let take = input.len().min(64 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&input[..take]);
+            self.buffered += take;
+            input = &input[take..];
+
+            if self.buffered < 64 {
+                return;
+            }
+
+            compress(&self.buffer);
+            self.buffered = 0;
+        }
+
+        let mut blocks = input.chunks_exact(64);
+        for block in &mut blocks {
+            compress(block);
+        }
+
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    // a single 1 bit, zeroes up to 56 bytes into the block, then the length in bits
+    pub fn finalize<F: FnMut(&[u8])>(mut self, endian : Endian, mut compress : F) {
+        let bit_length = self.length.wrapping_mul(8);
+
+        self.buffer[self.buffered] = 0x80;
+        for byte in self.buffer[self.buffered + 1..].iter_mut() {
+            *byte = 0;
+        }
+
+        // no room left for the length, it goes in a block of its own
+        if self.buffered >= 56 {
+            compress(&self.buffer);
+            self.buffer = [0; 64];
+        }
+
+        let length = match endian {
+            Endian::Little => bit_length.to_le_bytes(),
+            Endian::Big => bit_length.to_be_bytes(),
+        };
+        self.buffer[56..].copy_from_slice(&length);
+        compress(&self.buffer);
+    }
+}
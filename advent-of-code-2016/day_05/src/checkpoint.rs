@@ -0,0 +1,248 @@
+use door::{ DoorConfig, Password };
+use hash_algorithm::Algorithm;
+use search::Hit;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+
+// Everything needed to pick a search back up after it got killed. Saved as
+// plain text, one `key value` pair per line:
+//
+//   door reyedfim
+//   hash md5
+//   stretch 0
+//   zeroes 5
+//   searched 5017308
+//   password 18______
+//   password _5______
+//   hit 3231929 00000155f8105dff7f56ee10fa9b9abd
+//   hit 5017308 000008f82c5b3924a1ecbebf60344e00
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub door_id: String,
+    pub algorithm: Algorithm,
+    pub stretch: usize,
+    pub zeroes: usize,
+    // every index up to and including this one has been searched
+    pub searched: Option<u64>,
+    // characters found so far, one password per door
+    pub passwords: Vec<String>,
+    pub hits: Vec<Hit>,
+}
+
+impl Checkpoint {
+    pub fn new(door_id : &str, algorithm : Algorithm, stretch : usize, zeroes : usize) -> Checkpoint {
+        Checkpoint {
+            door_id: door_id.to_string(),
+            algorithm,
+            stretch,
+            zeroes,
+            searched: None,
+            passwords: Vec::new(),
+            hits: Vec::new(),
+        }
+    }
+
+    // first index that still has to be searched
+    pub fn next_index(&self) -> u64 {
+        self.searched.map_or(0, |index| index + 1)
+    }
+
+    pub fn record(&mut self, hit : Hit, passwords : &[Password]) {
+        self.searched = Some(hit.index);
+        self.passwords = passwords.iter().map(|p| p.to_partial_string()).collect();
+        self.hits.push(hit);
+    }
+
+    // every index below `next` has been searched, with no hits beyond the recorded ones
+    pub fn searched_before(&mut self, next : u64) {
+        if next > self.next_index() {
+            self.searched = Some(next - 1);
+        }
+    }
+
+    // can this checkpoint continue a search with the settings of `other`?
+    pub fn check_settings(&self, other : &Checkpoint) -> Result<(), String> {
+        let mine = (&self.door_id, self.algorithm, self.stretch, self.zeroes);
+        let theirs = (&other.door_id, other.algorithm, other.stretch, other.zeroes);
+
+        if mine != theirs {
+            return Err(format!(
+                "checkpoint is for door {} with {}, stretch {} and {} zeroes, not door {} with {}, stretch {} and {} zeroes",
+                self.door_id, self.algorithm, self.stretch, self.zeroes,
+                other.door_id, other.algorithm, other.stretch, other.zeroes));
+        }
+
+        Ok(())
+    }
+
+    // feeds the recorded hits to fresh passwords, they should end up where the checkpoint says
+    pub fn replay(&self, configs : &[DoorConfig]) -> Result<Vec<Password>, String> {
+        let mut passwords : Vec<Password> = configs.iter().map(Password::new).collect();
+        for hit in &self.hits {
+            for password in passwords.iter_mut() {
+                password.feed(hit);
+            }
+        }
+
+        let replayed : Vec<String> = passwords.iter().map(|p| p.to_partial_string()).collect();
+        if !self.hits.is_empty() && replayed != self.passwords {
+            return Err(format!("checkpoint passwords {:?} don't match its hits, which give {:?}", self.passwords, replayed));
+        }
+
+        Ok(passwords)
+    }
+
+    // `None` when there is no checkpoint yet
+    pub fn load(path : &str) -> Result<Option<Checkpoint>, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Checkpoint::parse(&contents).map(Some).map_err(|err| format!("{}: {}", path, err)),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("could not read {}: {}", path, err)),
+        }
+    }
+
+    // writes to a temporary file first, so getting killed halfway leaves the old checkpoint intact
+    pub fn save(&self, path : &str) -> Result<(), String> {
+        let temporary = format!("{}.tmp", path);
+        fs::write(&temporary, self.to_string())
+            .and_then(|_| fs::rename(&temporary, path))
+            .map_err(|err| format!("could not write {}: {}", path, err))
+    }
+
+    pub fn parse(contents : &str) -> Result<Checkpoint, String> {
+        let mut door_id = None;
+        let mut algorithm = None;
+        let mut stretch = None;
+        let mut zeroes = None;
+        let mut searched = None;
+        let mut passwords = Vec::new();
+        let mut hits = Vec::new();
+
+        for (number, line) in contents.lines().enumerate().filter(|&(_, l)| !l.trim().is_empty()) {
+            let fields : Vec<&str> = line.split_whitespace().collect();
+            let invalid = || format!("invalid line {}: '{}'", number + 1, line);
+
+            match (fields[0], fields.len()) {
+                ("door", 2) => door_id = Some(fields[1].to_string()),
+                ("hash", 2) => algorithm = Some(fields[1].parse().map_err(|err| format!("{}, {}", invalid(), err))?),
+                ("stretch", 2) => stretch = Some(fields[1].parse().map_err(|_| invalid())?),
+                ("zeroes", 2) => zeroes = Some(fields[1].parse().map_err(|_| invalid())?),
+                ("searched", 2) => searched = Some(fields[1].parse().map_err(|_| invalid())?),
+                ("password", 2) => passwords.push(fields[1].to_string()),
+                ("hit", 3) => hits.push(Hit {
+                    index: fields[1].parse().map_err(|_| invalid())?,
+                    hash: fields[2].to_string(),
+                }),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let missing = |key| format!("missing '{}' line", key);
+        Ok(Checkpoint {
+            door_id: door_id.ok_or_else(|| missing("door"))?,
+            algorithm: algorithm.ok_or_else(|| missing("hash"))?,
+            stretch: stretch.ok_or_else(|| missing("stretch"))?,
+            zeroes: zeroes.ok_or_else(|| missing("zeroes"))?,
+            searched,
+            passwords,
+            hits,
+        })
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "door {}", self.door_id)?;
+        writeln!(f, "hash {}", self.algorithm)?;
+        writeln!(f, "stretch {}", self.stretch)?;
+        writeln!(f, "zeroes {}", self.zeroes)?;
+
+        if let Some(searched) = self.searched {
+            writeln!(f, "searched {}", searched)?;
+        }
+
+        for password in &self.passwords {
+            writeln!(f, "password {}", password)?;
+        }
+
+        for hit in &self.hits {
+            writeln!(f, "hit {} {}", hit.index, hit.hash)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod round_trip {
+    use checkpoint::Checkpoint;
+    use door::{ DoorConfig, Password };
+    use hash_algorithm::Algorithm;
+    use search::Hit;
+
+    fn example() -> Checkpoint {
+        let configs = [DoorConfig::part_a(), DoorConfig::part_b()];
+        let mut passwords : Vec<Password> = configs.iter().map(Password::new).collect();
+        let mut checkpoint = Checkpoint::new("abc", Algorithm::Md5, 0, 5);
+
+        for &(index, hash) in &[(3231929, "00000155f8105dff7f56ee10fa9b9abd"), (5017308, "000008f82c5b3924a1ecbebf60344e00")] {
+            let hit = Hit { index, hash: hash.to_string() };
+            for password in passwords.iter_mut() {
+                password.feed(&hit);
+            }
+            checkpoint.record(hit, &passwords);
+        }
+
+        checkpoint
+    }
+
+    #[test]
+    fn it_parses_what_it_writes() {
+        let checkpoint = example();
+        assert_eq!(checkpoint.passwords, vec!["18______", "_5______"]);
+        assert_eq!(checkpoint.next_index(), 5017309);
+        assert_eq!(Checkpoint::parse(&checkpoint.to_string()), Ok(checkpoint));
+    }
+
+    #[test]
+    fn it_records_progress_between_hits() {
+        let mut checkpoint = example();
+        checkpoint.searched_before(6_000_000);
+        assert_eq!(checkpoint.next_index(), 6_000_000);
+
+        // progress never goes backwards
+        checkpoint.searched_before(5_500_000);
+        assert_eq!(checkpoint.next_index(), 6_000_000);
+        assert_eq!(Checkpoint::parse(&checkpoint.to_string()), Ok(checkpoint));
+    }
+
+    #[test]
+    fn it_replays_its_hits() {
+        let checkpoint = example();
+        let passwords = checkpoint.replay(&[DoorConfig::part_a(), DoorConfig::part_b()]).unwrap();
+        let replayed : Vec<String> = passwords.iter().map(|p| p.to_partial_string()).collect();
+        assert_eq!(replayed, checkpoint.passwords);
+
+        let mut tampered = checkpoint.clone();
+        tampered.passwords[1] = "_6______".to_string();
+        assert!(tampered.replay(&[DoorConfig::part_a(), DoorConfig::part_b()]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_other_settings() {
+        let checkpoint = example();
+        assert_eq!(checkpoint.check_settings(&Checkpoint::new("abc", Algorithm::Md5, 0, 5)), Ok(()));
+        assert!(checkpoint.check_settings(&Checkpoint::new("abd", Algorithm::Md5, 0, 5)).is_err());
+        assert!(checkpoint.check_settings(&Checkpoint::new("abc", Algorithm::Sha1, 0, 5)).is_err());
+        assert!(checkpoint.check_settings(&Checkpoint::new("abc", Algorithm::Md5, 2016, 5)).is_err());
+    }
+
+    #[test]
+    fn it_rejects_broken_files() {
+        assert!(Checkpoint::parse("door abc\nhash md5\nstretch 0\n").is_err());
+        let err = Checkpoint::parse("door abc\nhash md4\nstretch 0\nzeroes 5\n").unwrap_err();
+        assert!(err.starts_with("invalid line 2: 'hash md4'"), "{}", err);
+        assert!(Checkpoint::parse("door abc\nhash md5\nstretch 0\nzeroes 5\nhit 12\n").is_err());
+    }
+}
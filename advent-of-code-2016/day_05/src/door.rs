@@ -0,0 +1,248 @@
+use search::Hit;
+
+// how a door turns interesting hashes into a password
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoorConfig {
+    // leading zero nibbles for a hash to be interesting
+    pub zeroes: usize,
+    pub length: usize,
+    // `None` fills the password left to right. Otherwise the nibble right
+    // after the zeroes is looked up in this alphabet to find the position.
+    pub positions: Option<String>,
+    // nibbles between the zeroes and the password character
+    pub value_offset: usize,
+}
+
+impl DoorConfig {
+    pub fn part_a() -> DoorConfig {
+        DoorConfig { zeroes: 5, length: 8, positions: None, value_offset: 0 }
+    }
+
+    pub fn part_b() -> DoorConfig {
+        DoorConfig { zeroes: 5, length: 8, positions: Some("01234567".to_string()), value_offset: 1 }
+    }
+
+    // `digest_len` is the length in bytes of the hashes the door will see
+    pub fn validate(&self, digest_len : usize) -> Result<(), String> {
+        if self.length == 0 {
+            return Err("password length should be at least 1".to_string());
+        }
+
+        if self.zeroes + self.value_offset >= digest_len * 2 {
+            return Err(format!("{} zeroes and value offset {} don't fit in a {} byte hash", self.zeroes, self.value_offset, digest_len));
+        }
+
+        if let Some(ref positions) = self.positions {
+            if self.value_offset == 0 {
+                return Err("value offset should skip the position nibble".to_string());
+            }
+
+            let mut chars : Vec<char> = positions.chars().collect();
+            if chars.len() < self.length {
+                return Err(format!("position alphabet '{}' can't address {} characters", positions, self.length));
+            }
+
+            if let Some(c) = chars.iter().find(|c| !c.is_ascii_hexdigit() || c.is_ascii_uppercase()) {
+                return Err(format!("position alphabet should be lowercase hex, found '{}'", c));
+            }
+
+            chars.sort();
+            chars.dedup();
+            if chars.len() != positions.len() {
+                return Err(format!("position alphabet '{}' contains duplicates", positions));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// password that is filled in one hit at a time
+#[derive(Debug, Clone)]
+pub struct Password {
+    config: DoorConfig,
+    chars: Vec<Option<char>>,
+}
+
+impl Password {
+    pub fn new(config : &DoorConfig) -> Password {
+        Password { config: config.clone(), chars: vec![None; config.length] }
+    }
+
+    // fills in the character this hit points to, if any, and returns its position
+    pub fn feed(&mut self, hit : &Hit) -> Option<(usize, char)> {
+        let hash = hit.hash.as_bytes();
+
+        // hits can come from a search shared with doors that need fewer zeroes
+        if hash.len() < self.config.zeroes || hash[..self.config.zeroes].iter().any(|&b| b != b'0') {
+            return None;
+        }
+
+        let value = *hash.get(self.config.zeroes + self.config.value_offset)? as char;
+
+        let location = match self.config.positions {
+            Some(ref positions) => {
+                let nibble = *hash.get(self.config.zeroes)? as char;
+                positions.chars().position(|p| p == nibble)?
+            }
+            None => self.chars.iter().position(|c| c.is_none())?,
+        };
+
+        // ignore invalid positions, and positions we already know
+        if location >= self.chars.len() || self.chars[location].is_some() {
+            return None;
+        }
+
+        self.chars[location] = Some(value);
+        Some((location, value))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.chars.iter().all(|c| c.is_some())
+    }
+
+    // Every hit fills in a character when filling left to right. With
+    // positions, the position nibble is one of 16, so with `m` positions left
+    // a hit is new with chance m/16, and on average 16/m hits are needed for the next one.
+    pub fn expected_hits_left(&self) -> f64 {
+        let missing = self.chars.iter().filter(|c| c.is_none()).count();
+        match self.config.positions {
+            Some(_) => (1..=missing).map(|m| 16.0 / m as f64).sum(),
+            None => missing as f64,
+        }
+    }
+
+    // unknown characters are shown as `_`
+    pub fn to_partial_string(&self) -> String {
+        self.chars.iter().map(|c| c.unwrap_or('_')).collect()
+    }
+}
+
+// feeds hits until the password is complete, `on_found` is called for every new character
+pub fn solve<I, F>(config : &DoorConfig, hits : I, mut on_found : F) -> Option<String>
+    where I: Iterator<Item = Hit>,
+          F: FnMut(usize, char, &Password)
+{
+    let mut password = Password::new(config);
+
+    for hit in hits {
+        if let Some((location, character)) = password.feed(&hit) {
+            on_found(location, character, &password);
+        }
+
+        if password.is_complete() {
+            return Some(password.to_partial_string());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod validate {
+    use door::DoorConfig;
+
+    #[test]
+    fn it_accepts_both_parts() {
+        assert_eq!(DoorConfig::part_a().validate(16), Ok(()));
+        assert_eq!(DoorConfig::part_b().validate(16), Ok(()));
+
+        let hex = DoorConfig { zeroes: 6, length: 16, positions: Some("0123456789abcdef".to_string()), value_offset: 1 };
+        assert_eq!(hex.validate(16), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_invalid_configs() {
+        let invalid = vec![
+            DoorConfig { length: 0, ..DoorConfig::part_a() },
+            DoorConfig { zeroes: 32, ..DoorConfig::part_a() },
+            DoorConfig { length: 9, ..DoorConfig::part_b() },
+            DoorConfig { value_offset: 0, ..DoorConfig::part_b() },
+            DoorConfig { positions: Some("0123456g".to_string()), ..DoorConfig::part_b() },
+            DoorConfig { positions: Some("01234566".to_string()), ..DoorConfig::part_b() },
+        ];
+
+        for config in invalid {
+            assert!(config.validate(16).is_err(), "{:?} should be invalid", config);
+        }
+    }
+
+    #[test]
+    fn it_allows_more_zeroes_for_longer_digests() {
+        let config = DoorConfig { zeroes: 40, ..DoorConfig::part_a() };
+        assert!(config.validate(16).is_err());
+        assert!(config.validate(20).is_err());
+        assert_eq!(config.validate(32), Ok(()));
+    }
+}
+
+#[cfg(test)]
+mod password {
+    use door::{ DoorConfig, Password };
+    use search::Hit;
+
+    #[test]
+    fn it_ignores_hits_with_too_few_zeroes() {
+        let mut password = Password::new(&DoorConfig { zeroes: 6, ..DoorConfig::part_a() });
+        assert_eq!(password.feed(&Hit { index: 0, hash: "000001a".to_string() }), None);
+        assert_eq!(password.feed(&Hit { index: 1, hash: "000000a".to_string() }), Some((0, 'a')));
+    }
+
+    #[test]
+    fn it_expects_more_hits_for_positions() {
+        let mut part_a = Password::new(&DoorConfig::part_a());
+        assert_eq!(part_a.expected_hits_left(), 8.0);
+        part_a.feed(&Hit { index: 0, hash: "000001".to_string() });
+        assert_eq!(part_a.expected_hits_left(), 7.0);
+
+        let mut part_b = Password::new(&DoorConfig { length: 2, ..DoorConfig::part_b() });
+        assert_eq!(part_b.expected_hits_left(), 24.0);
+        part_b.feed(&Hit { index: 0, hash: "0000015".to_string() });
+        assert_eq!(part_b.expected_hits_left(), 16.0);
+    }
+}
+
+#[cfg(test)]
+mod solve {
+    use door::{ solve, DoorConfig };
+    use search::Hit;
+
+    fn hits(hashes : &[&str]) -> Vec<Hit> {
+        hashes.iter().enumerate()
+            .map(|(index, hash)| Hit { index: index as u64, hash: hash.to_string() })
+            .collect()
+    }
+
+    #[test]
+    fn it_fills_in_order_for_part_a() {
+        let hits = hits(&["000001", "000008", "00000f", "00000a"]);
+        let config = DoorConfig { length: 3, ..DoorConfig::part_a() };
+        assert_eq!(solve(&config, hits.into_iter(), |_, _, _| {}), Some("18f".to_string()));
+    }
+
+    #[test]
+    fn it_fills_by_position_for_part_b() {
+        // example from the puzzle: 8 is an invalid position, 1 is already taken
+        let hits = hits(&["0000015", "000008f", "000004e", "000001a", "0000000", "0000023", "0000035", "000005c", "0000068", "0000079"]);
+        let mut found = Vec::new();
+        let pass = solve(&DoorConfig::part_b(), hits.into_iter(), |location, c, _| found.push((location, c)));
+
+        assert_eq!(pass, Some("0535ec89".to_string()));
+        assert_eq!(found[0], (1, '5'));
+        assert_eq!(found[1], (4, 'e'));
+    }
+
+    #[test]
+    fn it_supports_hex_positions() {
+        let config = DoorConfig { zeroes: 1, length: 16, positions: Some("0123456789abcdef".to_string()), value_offset: 1 };
+        let hashes : Vec<String> = "0123456789abcdef".chars().rev().map(|p| format!("0{}{}", p, p)).collect();
+        let hashes : Vec<&str> = hashes.iter().map(|h| h.as_str()).collect();
+
+        assert_eq!(solve(&config, hits(&hashes).into_iter(), |_, _, _| {}), Some("0123456789abcdef".to_string()));
+    }
+
+    #[test]
+    fn it_gives_up_when_hits_run_out() {
+        assert_eq!(solve(&DoorConfig::part_b(), hits(&["0000015"]).into_iter(), |_, _, _| {}), None);
+    }
+}
@@ -0,0 +1,114 @@
+use md5::Md5;
+use sha1::Sha1;
+use sha256::Sha256;
+use std::fmt;
+use std::str::FromStr;
+
+// streaming hash function the door search can run on. Hashers are `Copy`,
+// so a hasher that absorbed the door id can be reused as a midstate.
+pub trait HashAlgorithm: Copy + Send + 'static {
+    type Digest: AsRef<[u8]> + Copy + Default;
+
+    fn new() -> Self;
+    fn update(&mut self, input : &[u8]);
+    fn finalize(self) -> Self::Digest;
+}
+
+impl HashAlgorithm for Md5 {
+    type Digest = [u8; 16];
+
+    fn new() -> Md5 { Md5::new() }
+    fn update(&mut self, input : &[u8]) { self.update(input) }
+    fn finalize(self) -> [u8; 16] { self.finalize() }
+}
+
+impl HashAlgorithm for Sha1 {
+    type Digest = [u8; 20];
+
+    fn new() -> Sha1 { Sha1::new() }
+    fn update(&mut self, input : &[u8]) { self.update(input) }
+    fn finalize(self) -> [u8; 20] { self.finalize() }
+}
+
+impl HashAlgorithm for Sha256 {
+    type Digest = [u8; 32];
+
+    fn new() -> Sha256 { Sha256::new() }
+    fn update(&mut self, input : &[u8]) { self.update(input) }
+    fn finalize(self) -> [u8; 32] { self.finalize() }
+}
+
+// hash algorithm picked at runtime
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl Algorithm {
+    // digest length in bytes
+    pub fn digest_len(&self) -> usize {
+        match *self {
+            Algorithm::Md5 => 16,
+            Algorithm::Sha1 => 20,
+            Algorithm::Sha256 => 32,
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha256 => "sha256",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md5" => Ok(Algorithm::Md5),
+            "sha1" => Ok(Algorithm::Sha1),
+            "sha256" => Ok(Algorithm::Sha256),
+            _ => Err(format!("unknown hash algorithm '{}', expected md5, sha1 or sha256", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod algorithm {
+    use hash_algorithm::{ Algorithm, HashAlgorithm };
+    use md5::Md5;
+    use sha1::Sha1;
+    use sha256::Sha256;
+
+    fn digest_len<H: HashAlgorithm>() -> usize {
+        let mut hasher = H::new();
+        hasher.update(b"abc");
+        hasher.finalize().as_ref().len()
+    }
+
+    #[test]
+    fn it_knows_the_digest_lengths() {
+        assert_eq!(digest_len::<Md5>(), Algorithm::Md5.digest_len());
+        assert_eq!(digest_len::<Sha1>(), Algorithm::Sha1.digest_len());
+        assert_eq!(digest_len::<Sha256>(), Algorithm::Sha256.digest_len());
+    }
+
+    #[test]
+    fn it_parses_algorithm_names() {
+        assert_eq!("md5".parse(), Ok(Algorithm::Md5));
+        assert_eq!("sha1".parse(), Ok(Algorithm::Sha1));
+        assert_eq!("sha256".parse(), Ok(Algorithm::Sha256));
+        assert!("sha512".parse::<Algorithm>().is_err());
+
+        for &algorithm in &[Algorithm::Md5, Algorithm::Sha1, Algorithm::Sha256] {
+            assert_eq!(algorithm.to_string().parse(), Ok(algorithm));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use hash_algorithm::HashAlgorithm;
+use md5::Md5;
+use md5_lanes::{ self, Backend, MAX_LANES };
+
+// longest decimal representation of a u64
+const MAX_DIGITS : usize = 20;
+
+// hex representation of the longest digest we support
+const MAX_HEX : usize = 64;
+
+const HEX : &[u8; 16] = b"0123456789abcdef";
+
+// Hashes door id + index without allocating. The door id is absorbed only
+// once, every index continues from a copy of that midstate, and the index
+// digits are written to a fixed buffer.
+pub struct DoorHasher<H: HashAlgorithm> {
+    door_id: H,
+    digits: [u8; MAX_DIGITS],
+    hex: [u8; MAX_HEX],
+    digest: H::Digest,
+    stretch: usize,
+}
+
+impl<H: HashAlgorithm> DoorHasher<H> {
+    pub fn new(door_id : &str) -> DoorHasher<H> {
+        DoorHasher::with_stretch(door_id, 0)
+    }
+
+    // key stretching: every digest is hashed again, as lowercase hex, `stretch` more times
+    pub fn with_stretch(door_id : &str, stretch : usize) -> DoorHasher<H> {
+        let mut prefix = H::new();
+        prefix.update(door_id.as_bytes());
+
+        DoorHasher {
+            door_id: prefix,
+            digits: [0; MAX_DIGITS],
+            hex: [0; MAX_HEX],
+            digest: H::Digest::default(),
+            stretch,
+        }
+    }
+
+    // raw digest of door id + index
+    pub fn digest(&mut self, index : u64) -> &[u8] {
+        let start = write_digits(index, &mut self.digits);
+
+        let mut hasher = self.door_id;
+        hasher.update(&self.digits[start..]);
+        self.digest = hasher.finalize();
+
+        for _ in 0..self.stretch {
+            let length = write_hex(self.digest.as_ref(), &mut self.hex);
+            let mut hasher = H::new();
+            hasher.update(&self.hex[..length]);
+            self.digest = hasher.finalize();
+        }
+
+        self.digest.as_ref()
+    }
+}
+
+// `DoorHasher` for MD5 that hashes a run of consecutive indices at once, one
+// per SIMD lane. Searches ask for indices in order, so the rest of the run is
+// usually what gets asked for next.
+pub struct LaneHasher {
+    door_id: Md5,
+    backend: Backend,
+    digits: [u8; MAX_DIGITS],
+    // index of digests[0], and how many digests are valid
+    first: u64,
+    count: usize,
+    digests: [[u8; 16]; MAX_LANES],
+}
+
+impl LaneHasher {
+    pub fn new(door_id : &str, backend : Backend) -> LaneHasher {
+        let mut prefix = Md5::new();
+        prefix.update(door_id.as_bytes());
+
+        LaneHasher {
+            door_id: prefix,
+            backend,
+            digits: [0; MAX_DIGITS],
+            first: 0,
+            count: 0,
+            digests: [[0; 16]; MAX_LANES],
+        }
+    }
+
+    // raw digest of door id + index, the same as `DoorHasher::<Md5>` gives
+    pub fn digest(&mut self, index : u64) -> &[u8] {
+        if index < self.first || index - self.first >= self.count as u64 {
+            self.hash_run(index);
+        }
+
+        &self.digests[(index - self.first) as usize]
+    }
+
+    fn hash_run(&mut self, first : u64) {
+        let count = (self.backend.lanes() as u64).min((u64::MAX - first).saturating_add(1)) as usize;
+
+        let mut hashers = [self.door_id; MAX_LANES];
+        for (offset, hasher) in hashers.iter_mut().enumerate().take(count) {
+            let start = write_digits(first + offset as u64, &mut self.digits);
+            hasher.update(&self.digits[start..]);
+        }
+
+        md5_lanes::finalize(self.backend, &hashers[..count], &mut self.digests[..count]);
+        self.first = first;
+        self.count = count;
+    }
+}
+
+// writes the lowercase hex representation of `digest` to `buffer`, returns its length
+fn write_hex(digest : &[u8], buffer : &mut [u8; MAX_HEX]) -> usize {
+    for (idx, byte) in digest.iter().enumerate() {
+        buffer[idx * 2] = HEX[(byte >> 4) as usize];
+        buffer[idx * 2 + 1] = HEX[(byte & 0xf) as usize];
+    }
+    digest.len() * 2
+}
+
+// writes the decimal digits of `n` to the end of `buffer`, returns where they start
+fn write_digits(mut n : u64, buffer : &mut [u8; MAX_DIGITS]) -> usize {
+    let mut start = MAX_DIGITS;
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+
+        if n == 0 {
+            return start;
+        }
+    }
+}
+
+// does the hex representation of the digest start with `count` zeroes?
+pub fn has_leading_zeroes(digest : &[u8], count : usize) -> bool {
+    let full_bytes = count / 2;
+    if digest.len() * 2 < count {
+        return false;
+    }
+
+    digest[..full_bytes].iter().all(|b| *b == 0) && (count.is_multiple_of(2) || digest[full_bytes] >> 4 == 0)
+}
+
+// lowercase hex representation of a digest
+pub fn to_hex(digest : &[u8]) -> String {
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        hex.push(HEX[(byte >> 4) as usize] as char);
+        hex.push(HEX[(byte & 0xf) as usize] as char);
+    }
+    hex
+}
+
+#[cfg(test)]
+mod door_hasher {
+    use hasher::{ to_hex, DoorHasher };
+    use md5::Md5;
+    use sha1::Sha1;
+    use sha256::Sha256;
+
+    #[test]
+    fn it_hashes_door_id_and_index() {
+        let mut hasher = DoorHasher::<Md5>::new("abc");
+        assert_eq!(to_hex(hasher.digest(3231929)), "00000155f8105dff7f56ee10fa9b9abd");
+        assert_eq!(to_hex(hasher.digest(0)), "577571be4de9dcce85a041ba0410f29f");
+        assert!(to_hex(hasher.digest(5017308)).starts_with("000008f82"));
+    }
+
+    #[test]
+    fn it_hashes_with_other_algorithms() {
+        let mut sha1 = DoorHasher::<Sha1>::new("abc");
+        assert_eq!(to_hex(sha1.digest(3231929)), "c9cfc9943801dafc36b556ac26126a44fec988ad");
+
+        let mut sha256 = DoorHasher::<Sha256>::new("abc");
+        assert_eq!(to_hex(sha256.digest(3231929)), "e8816b51e183ba3584d8dbcfc1457dffb4cd114eb3cd30735cc50be9abef3db0");
+    }
+}
+
+#[cfg(test)]
+mod lane_hasher {
+    use hasher::{ DoorHasher, LaneHasher };
+    use md5::Md5;
+    use md5_lanes::Backend;
+
+    #[test]
+    fn it_matches_the_door_hasher() {
+        let mut scalar = DoorHasher::<Md5>::new("abc");
+
+        // skipping around, and across the points where the number of digits changes
+        let indices : Vec<u64> = (0..20).chain(95..105).chain(3231920..3231940).chain((0..5).rev()).chain(u64::MAX - 5..=u64::MAX).collect();
+
+        for &backend in &[Backend::Portable, Backend::Sse2, Backend::Avx2] {
+            if !backend.is_supported() {
+                continue;
+            }
+
+            let mut lanes = LaneHasher::new("abc", backend);
+            for &index in &indices {
+                assert_eq!(lanes.digest(index), scalar.digest(index), "index {} on {}", index, backend);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod stretch {
+    use hasher::{ to_hex, DoorHasher };
+    use md5::Md5;
+
+    #[test]
+    fn it_rehashes_the_hex_digest() {
+        let mut plain = DoorHasher::<Md5>::with_stretch("abc", 0);
+        assert_eq!(to_hex(plain.digest(0)), "577571be4de9dcce85a041ba0410f29f");
+
+        let mut stretched = DoorHasher::<Md5>::with_stretch("abc", 2016);
+        assert_eq!(to_hex(stretched.digest(0)), "a107ff634856bb300138cac6568c0f24");
+    }
+}
+
+#[cfg(test)]
+mod write_digits {
+    use hasher::{ write_digits, MAX_DIGITS };
+
+    #[test]
+    fn it_writes_decimal_digits() {
+        let mut buffer = [0; MAX_DIGITS];
+        for &n in &[0, 7, 10, 3231929, u64::MAX] {
+            let start = write_digits(n, &mut buffer);
+            assert_eq!(&buffer[start..], n.to_string().as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod has_leading_zeroes {
+    use hasher::has_leading_zeroes;
+
+    #[test]
+    fn it_checks_nibbles() {
+        let digest = [0x00, 0x00, 0x0f, 0xff];
+        assert!(has_leading_zeroes(&digest, 4));
+        assert!(has_leading_zeroes(&digest, 5));
+        assert!(!has_leading_zeroes(&digest, 6));
+        assert!(has_leading_zeroes(&digest, 0));
+        assert!(!has_leading_zeroes(&[0, 0], 5));
+    }
+}
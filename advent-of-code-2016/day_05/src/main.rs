@@ -34,73 +34,275 @@ You almost choke on your popcorn as the final character falls into place, produc
 Given the actual Door ID and this new method, what is the password? Be extra proud of your solution if it uses a cinematic "decrypting" animation.
 */
 
-extern crate crypto;
-use crypto::md5::Md5;
-use crypto::digest::Digest;
-
-fn hash(input : &str) -> String {
-    let mut hash = Md5::new();
-    hash.input_str(&input);
-    hash.result_str()
+extern crate ctrlc;
+
+mod block;
+mod md5;
+mod md5_lanes;
+mod sha1;
+mod sha256;
+mod hash_algorithm;
+mod search;
+mod hasher;
+mod bench;
+mod door;
+mod checkpoint;
+mod progress;
+mod animation;
+
+use animation::Animation;
+use checkpoint::Checkpoint;
+use door::{ DoorConfig, Password };
+use hash_algorithm::{ Algorithm, HashAlgorithm };
+use hasher::{ has_leading_zeroes, to_hex, DoorHasher, LaneHasher };
+use md5_lanes::Backend;
+use progress::Progress;
+use search::{ Event, Hit, ParallelSearch };
+use std::env;
+use std::fmt::Display;
+use std::io::{ self, IsTerminal };
+use std::process;
+use std::str::FromStr;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+// indices every worker thread claims at once
+const BATCH_SIZE : u64 = 10_000;
+
+// how often progress gets reported and saved in between hits
+const UPDATE_INTERVAL : Duration = Duration::from_secs(2);
+
+// Only hits get formatted as hex, everything else stays on the raw digest.
+fn to_hit(index : u64, digest : &[u8], zeroes : usize) -> Option<Hit> {
+    if has_leading_zeroes(digest, zeroes) {
+        Some(Hit { index, hash: to_hex(digest) })
+    } else {
+        None
+    }
 }
 
-fn solve_part_a(puzzle_input : &str){
-    let mut pass = Vec::new();
+// checks whether the hash of door id + index is interesting
+fn checker<H: HashAlgorithm>(door_id : &str, zeroes : usize, stretch : usize) -> impl FnMut(u64) -> Option<Hit> {
+    let mut hasher = DoorHasher::<H>::with_stretch(door_id, stretch);
+    move |index| to_hit(index, hasher.digest(index), zeroes)
+}
 
-    // iterate forever until we break
-    for i in 0..std::u64::MAX {
-        let input : &str = &format!("{}{}", puzzle_input, i);
-        let result = hash(&input);
+// same, hashing a few indices at once on simd lanes
+fn lane_checker(door_id : &str, zeroes : usize, backend : Backend) -> impl FnMut(u64) -> Option<Hit> {
+    let mut hasher = LaneHasher::new(door_id, backend);
+    move |index| to_hit(index, hasher.digest(index), zeroes)
+}
 
-        if result.starts_with("00000") {
-            pass.push(result.chars().nth(5).unwrap());
+// how the index space of a door gets searched
+struct Search {
+    algorithm: Algorithm,
+    door_id: String,
+    stretch: usize,
+    threads: usize,
+    backend: Backend,
+}
 
-            // once the pass reaches 8 chars, break
-            if pass.len() == 8 {
-                break;
+impl Search {
+    // all interesting hashes for the door from index `start` on, in order of their
+    // index, with the progress of the search in between
+    fn events(&self, zeroes : usize, start : u64) -> Box<dyn Iterator<Item = Event>> {
+        match self.algorithm {
+            // stretching re-hashes one digest at a time, lanes only pay off without it
+            Algorithm::Md5 if self.stretch == 0 => {
+                let door_id = self.door_id.clone();
+                let backend = self.backend;
+                self.run(start, move || lane_checker(&door_id, zeroes, backend))
             }
+            Algorithm::Md5 => self.events_with::<md5::Md5>(zeroes, start),
+            Algorithm::Sha1 => self.events_with::<sha1::Sha1>(zeroes, start),
+            Algorithm::Sha256 => self.events_with::<sha256::Sha256>(zeroes, start),
         }
     }
 
-    // we got em. 😏
-    println!("[PART A] cracked pass: {:?}", pass);
+    fn events_with<H: HashAlgorithm>(&self, zeroes : usize, start : u64) -> Box<dyn Iterator<Item = Event>> {
+        let door_id = self.door_id.clone();
+        let stretch = self.stretch;
+        self.run(start, move || checker::<H>(&door_id, zeroes, stretch))
+    }
+
+    fn run<F, C>(&self, start : u64, make_check : F) -> Box<dyn Iterator<Item = Event>>
+        where F: Fn() -> C + Send + Sync + 'static,
+              C: FnMut(u64) -> Option<Hit> + 'static
+    {
+        if self.threads > 1 {
+            Box::new(ParallelSearch::new(self.threads, BATCH_SIZE, start, make_check))
+        } else {
+            Box::new(search::sequential(start, BATCH_SIZE, make_check()))
+        }
+    }
 }
 
-fn solve_part_b(puzzle_input : &str){
+// Solves both parts from a single stream of hits, so the index space is only
+// searched once. With `--checkpoint <path>` the search is saved after every
+// hit and every few seconds in between, and picks up where it left off when
+// restarted. `--animate` decrypts part B in place, as long as stdout is a
+// terminal, otherwise `--progress` reports how far along the search is on stderr.
+fn solve_parts(search : &Search, path : Option<&str>, animate : bool, report_progress : bool) -> Result<(), String> {
+    let configs = [DoorConfig::part_a(), DoorConfig::part_b()];
+    let zeroes = configs.iter().map(|c| c.zeroes).min().unwrap();
 
-    let mut pass = vec![None; 8];
+    let fresh = Checkpoint::new(&search.door_id, search.algorithm, search.stretch, zeroes);
+    let mut checkpoint = match path {
+        Some(path) => Checkpoint::load(path)?.unwrap_or_else(|| fresh.clone()),
+        None => fresh.clone(),
+    };
+    checkpoint.check_settings(&fresh)?;
+
+    let mut passwords = checkpoint.replay(&configs)?;
+    if let Some(searched) = checkpoint.searched {
+        println!("resuming after index {} with {:?}", searched, checkpoint.passwords);
+    }
 
-    // iterate forever until we break
-    for i in 0..std::u64::MAX {
-        let input : &str = &format!("{}{}", puzzle_input, i);
-        let result = hash(&input);
+    let progress = Progress::new(checkpoint.next_index());
+    let is_done = |passwords : &[Password]| passwords.iter().all(|p| p.is_complete());
 
-        if result.starts_with("00000") {
-            if let Some(location) = result.chars().nth(5).unwrap().to_digit(10) {
-                let location = location as usize;
-                if location > 7 { continue };
-                if pass.get(location).unwrap().is_some() { continue; }
+    let animation = if animate && io::stdout().is_terminal() {
+        Some(Animation::start(&passwords[1].to_partial_string()))
+    } else {
+        None
+    };
 
-                let character = result.chars().nth(6).unwrap();
-                pass[location] = result.chars().nth(6);
+    if !is_done(&passwords) {
+        let mut last_update = Instant::now();
 
-                println!("found character {} for location {}", character, location);
+        for event in search.events(zeroes, checkpoint.next_index()) {
+            let found = match event {
+                Event::Hit(hit) => {
+                    let found = passwords[1].feed(&hit);
+                    passwords[0].feed(&hit);
+                    checkpoint.record(hit, &passwords);
+                    Some(found)
+                }
+                Event::Searched(next) => {
+                    checkpoint.searched_before(next);
+                    None
+                }
+            };
+
+            if found.is_none() && last_update.elapsed() < UPDATE_INTERVAL {
+                continue;
             }
-        }
+            last_update = Instant::now();
 
-        // once all characters are filled in, break
-        if pass.iter().all(|c| c.is_some()) {
-            break;
+            if let Some(path) = path {
+                checkpoint.save(path)?;
+            }
+
+            let hits_left = passwords.iter().map(|p| p.expected_hits_left()).fold(0.0, f64::max);
+            let report = progress.report(checkpoint.next_index(), checkpoint.hits.len(), hits_left);
+
+            match animation {
+                Some(ref animation) => animation.update(&passwords[1].to_partial_string(), report.to_string()),
+                None => {
+                    if let Some(Some((location, character))) = found {
+                        println!("found character {} for location {}", character, location);
+                    }
+                    if report_progress {
+                        eprintln!("{}", report);
+                    }
+                }
+            }
+
+            if is_done(&passwords) {
+                break;
+            }
         }
     }
 
+    if let Some(animation) = animation {
+        animation.finish();
+    }
+
     // we got em. 😏
-    let pass_string : String = pass.iter().map(|c| c.unwrap()).collect();
-    println!("[PART B] cracked the pass: {:?}", pass_string);
+    println!("[PART A] cracked pass: {:?}", passwords[0].to_partial_string());
+    println!("[PART B] cracked the pass: {:?}", passwords[1].to_partial_string());
+    Ok(())
+}
+
+// `--zeroes <n> --length <n> --positions <alphabet> --value-offset <n>` solves
+// a custom door, any option that is left out is taken from part B
+fn solve_custom<I: Iterator<Item = Hit>>(config : &DoorConfig, hits : I) {
+    let pass = door::solve(config, hits, |_, _, password| {
+        println!("{}", password.to_partial_string());
+    });
+
+    match pass {
+        Some(pass) => println!("[CUSTOM] cracked the pass: {:?}", pass),
+        None => println!("[CUSTOM] ran out of indices"),
+    }
+}
+
+// value following `name` in the arguments, if it was given
+fn option<T>(args : &[String], name : &str) -> Result<Option<T>, String>
+    where T: FromStr, T::Err: Display
+{
+    match args.iter().position(|a| a == name) {
+        None => Ok(None),
+        Some(idx) => args.get(idx + 1)
+            .ok_or_else(|| format!("missing value for {}", name))
+            .and_then(|v| v.parse().map(Some).map_err(|err| format!("invalid value for {}: {}", name, err))),
+    }
+}
+
+fn exit_with(message : &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
 }
 
 fn main() {
-    const PUZZLE_INPUT : &'static str = "reyedfim";
-    solve_part_a(PUZZLE_INPUT);
-    solve_part_b(PUZZLE_INPUT);
+    let args : Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        exit_with(&err);
+    }
+}
+
+fn run(args : &[String]) -> Result<(), String> {
+    const PUZZLE_INPUT : &str = "reyedfim";
+
+    // `bench` compares the hashing loop against formatting and allocating every index
+    if args.first().map(|a| a.as_str()) == Some("bench") {
+        bench::run(PUZZLE_INPUT, 1_000_000);
+        return Ok(());
+    }
+
+    // `--threads <n>` overrides the number of worker threads
+    let threads = option(args, "--threads")?
+        .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+
+    // `--hash md5|sha1|sha256` picks the hash algorithm
+    let algorithm : Algorithm = option(args, "--hash")?.unwrap_or(Algorithm::Md5);
+
+    // `--stretch <n>` re-hashes every digest as hex n more times
+    let stretch = option(args, "--stretch")?.unwrap_or(0);
+
+    // `--simd auto|portable|sse2|avx2` picks how many md5 hashes run side by side
+    let backend = option(args, "--simd")?.unwrap_or_else(Backend::detect);
+
+    let search = Search { algorithm, door_id: PUZZLE_INPUT.to_string(), stretch, threads, backend };
+
+    let custom = ["--zeroes", "--length", "--positions", "--value-offset"];
+    if args.iter().any(|a| custom.contains(&a.as_str())) {
+        let part_b = DoorConfig::part_b();
+        let config = DoorConfig {
+            zeroes: option(args, "--zeroes")?.unwrap_or(part_b.zeroes),
+            length: option(args, "--length")?.unwrap_or(part_b.length),
+            positions: option(args, "--positions")?.or(part_b.positions),
+            value_offset: option(args, "--value-offset")?.unwrap_or(part_b.value_offset),
+        };
+
+        config.validate(algorithm.digest_len()).map_err(|err| format!("invalid door config: {}", err))?;
+
+        solve_custom(&config, search.events(config.zeroes, 0).filter_map(Event::hit));
+        return Ok(());
+    }
+
+    let path : Option<String> = option(args, "--checkpoint")?;
+    let animate = args.iter().any(|a| a == "--animate");
+    let report_progress = args.iter().any(|a| a == "--progress");
+    solve_parts(&search, path.as_deref(), animate, report_progress)
 }
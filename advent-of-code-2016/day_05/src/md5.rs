@@ -0,0 +1,177 @@
+// MD5 as described in RFC 1321. Hashing is streaming: `update` can be called
+// any number of times before `finalize`. Cloning a hasher clones its midstate,
+// so a shared prefix only has to be absorbed once.
+
+use block::{ BlockBuffer, Endian };
+
+// per-round shift amounts
+pub const S : [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+// floor(abs(sin(i + 1)) * 2^32)
+pub const K : [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+const INITIAL_STATE : [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Md5 {
+    state: [u32; 4],
+    blocks: BlockBuffer,
+}
+
+impl Default for Md5 {
+    fn default() -> Md5 {
+        Md5::new()
+    }
+}
+
+impl Md5 {
+    pub fn new() -> Md5 {
+        Md5 { state: INITIAL_STATE, blocks: BlockBuffer::new() }
+    }
+
+    pub fn update(&mut self, input : &[u8]) {
+        let state = &mut self.state;
+        self.blocks.update(input, |block| compress(state, block));
+    }
+
+    pub fn finalize(mut self) -> [u8; 16] {
+        let state = &mut self.state;
+        self.blocks.finalize(Endian::Little, |block| compress(state, block));
+        to_digest(&self.state)
+    }
+
+    // The blocks `finalize` still has to compress, without compressing them,
+    // so several hashers can be finished side by side.
+    pub fn final_blocks(self) -> FinalBlocks {
+        let mut blocks = [[0u8; 64]; 2];
+        let mut count = 0;
+        self.blocks.finalize(Endian::Little, |block| {
+            blocks[count].copy_from_slice(block);
+            count += 1;
+        });
+
+        FinalBlocks { state: self.state, blocks, count }
+    }
+}
+
+// padding ends up in one block, or two when the length doesn't fit anymore
+#[derive(Debug, Clone, Copy)]
+pub struct FinalBlocks {
+    pub state: [u32; 4],
+    pub blocks: [[u8; 64]; 2],
+    pub count: usize,
+}
+
+pub fn to_digest(state : &[u32; 4]) -> [u8; 16] {
+    let mut digest = [0u8; 16];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+// md5 of `input` in one go
+pub fn digest(input : &[u8]) -> [u8; 16] {
+    let mut md5 = Md5::new();
+    md5.update(input);
+    md5.finalize()
+}
+
+fn compress(state : &mut [u32; 4], block : &[u8]) {
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+
+    // one loop per round, so the round function doesn't have to be picked every step
+    macro_rules! round {
+        ($range:expr, $f:expr, $g:expr) => {
+            for i in $range {
+                let f : u32 = $f(b, c, d);
+                let rotated = a.wrapping_add(f).wrapping_add(K[i]).wrapping_add(m[$g(i)]).rotate_left(S[i]);
+                a = d;
+                d = c;
+                c = b;
+                b = b.wrapping_add(rotated);
+            }
+        };
+    }
+
+    round!(0..16, |b: u32, c: u32, d: u32| (b & c) | (!b & d), |i: usize| i);
+    round!(16..32, |b: u32, c: u32, d: u32| (d & b) | (!d & c), |i: usize| (5 * i + 1) % 16);
+    round!(32..48, |b: u32, c: u32, d: u32| b ^ c ^ d, |i: usize| (3 * i + 5) % 16);
+    round!(48..64, |b: u32, c: u32, d: u32| c ^ (b | !d), |i: usize| (7 * i) % 16);
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+}
+
+#[cfg(test)]
+mod rfc_1321 {
+    use md5::{ digest, Md5 };
+    use hasher::to_hex;
+
+    // test suite from appendix A.5 of RFC 1321
+    const SUITE : [(&str, &str); 7] = [
+        ("", "d41d8cd98f00b204e9800998ecf8427e"),
+        ("a", "0cc175b9c0f1b6a831c399e269772661"),
+        ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+        ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+        ("abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"),
+        ("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789", "d174ab98d277d9f5a5611c2c9f419d9f"),
+        ("12345678901234567890123456789012345678901234567890123456789012345678901234567890", "57edf4a22be3c955ac49da2e2107b67a"),
+    ];
+
+    #[test]
+    fn it_passes_the_test_suite() {
+        for &(input, expected) in SUITE.iter() {
+            assert_eq!(to_hex(&digest(input.as_bytes())), expected, "md5({:?})", input);
+        }
+    }
+
+    #[test]
+    fn it_streams_in_any_chunk_size() {
+        for &(input, expected) in SUITE.iter() {
+            for chunk_size in 1..70 {
+                let mut md5 = Md5::new();
+                for chunk in input.as_bytes().chunks(chunk_size) {
+                    md5.update(chunk);
+                }
+                assert_eq!(to_hex(&md5.finalize()), expected, "md5({:?}) in chunks of {}", input, chunk_size);
+            }
+        }
+    }
+
+    #[test]
+    fn it_clones_the_midstate() {
+        let mut prefix = Md5::new();
+        prefix.update(b"abc");
+
+        let mut first = prefix;
+        first.update(b"3231929");
+        let mut second = prefix;
+        second.update(b"5017308");
+
+        assert_eq!(to_hex(&first.finalize()), "00000155f8105dff7f56ee10fa9b9abd");
+        assert_eq!(to_hex(&second.finalize()), "000008f82c5b3924a1ecbebf60344e00");
+        assert_eq!(to_hex(&prefix.finalize()), "900150983cd24fb0d6963f7d28e17f72");
+    }
+}
@@ -0,0 +1,374 @@
+// Finishes several MD5 hashers at once, one per SIMD lane: SSE2 hashes 4
+// messages side by side and AVX2 hashes 8. Lane `n` of every vector belongs to
+// message `n`, so the rounds are exactly those of `md5::compress`, just on
+// vectors of words. Messages whose padding doesn't take the same number of
+// blocks as the rest go through the scalar path instead.
+
+use md5::{ to_digest, FinalBlocks, Md5, K, S };
+use std::fmt;
+use std::str::FromStr;
+
+// most lanes any backend hashes at once
+pub const MAX_LANES : usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    // plain arrays, for any target
+    Portable,
+    Sse2,
+    Avx2,
+}
+
+impl Backend {
+    // the widest backend this cpu supports
+    pub fn detect() -> Backend {
+        if Backend::Avx2.is_supported() {
+            Backend::Avx2
+        } else if Backend::Sse2.is_supported() {
+            Backend::Sse2
+        } else {
+            Backend::Portable
+        }
+    }
+
+    pub fn lanes(&self) -> usize {
+        match *self {
+            Backend::Portable | Backend::Sse2 => 4,
+            Backend::Avx2 => 8,
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub fn is_supported(&self) -> bool {
+        match *self {
+            // part of the x86-64 baseline
+            Backend::Portable | Backend::Sse2 => true,
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+        }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    pub fn is_supported(&self) -> bool {
+        *self == Backend::Portable
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Backend::Portable => "portable",
+            Backend::Sse2 => "sse2",
+            Backend::Avx2 => "avx2",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let backend = match s {
+            "auto" => Backend::detect(),
+            "portable" => Backend::Portable,
+            "sse2" => Backend::Sse2,
+            "avx2" => Backend::Avx2,
+            _ => return Err(format!("unknown simd backend '{}', expected auto, portable, sse2 or avx2", s)),
+        };
+
+        if !backend.is_supported() {
+            return Err(format!("this cpu doesn't support {}", backend));
+        }
+
+        Ok(backend)
+    }
+}
+
+// Finalizes every hasher, `backend.lanes()` at a time. Gives exactly the
+// digests `Md5::finalize` gives. Backends the cpu doesn't support fall back to portable.
+pub fn finalize(backend : Backend, hashers : &[Md5], digests : &mut [[u8; 16]]) {
+    let backend = if backend.is_supported() { backend } else { Backend::Portable };
+    let lanes = backend.lanes();
+
+    for (hashers, digests) in hashers.chunks(lanes).zip(digests.chunks_mut(lanes)) {
+        let mut finals = [Md5::new().final_blocks(); MAX_LANES];
+        for (last, hasher) in finals.iter_mut().zip(hashers) {
+            *last = hasher.final_blocks();
+        }
+
+        let finals = &finals[..hashers.len()];
+        let uniform = hashers.len() == lanes && finals.iter().all(|f| f.count == finals[0].count);
+        if !uniform {
+            for (digest, hasher) in digests.iter_mut().zip(hashers) {
+                *digest = hasher.finalize();
+            }
+            continue;
+        }
+
+        match backend {
+            Backend::Portable => finalize_lanes::<Portable<4>, 4>(finals, digests),
+            Backend::Sse2 => sse2(finals, digests),
+            Backend::Avx2 => avx2(finals, digests),
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn sse2(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+    finalize_lanes::<x86::Sse2, 4>(finals, digests)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn avx2(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+    // only picked after checking the cpu supports avx2
+    unsafe { x86::finalize_avx2(finals, digests) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn sse2(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+    finalize_lanes::<Portable<4>, 4>(finals, digests)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn avx2(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+    finalize_lanes::<Portable<8>, 8>(finals, digests)
+}
+
+// N words, one per lane
+trait Lanes<const N : usize>: Copy {
+    fn splat(word : u32) -> Self;
+    fn load(words : [u32; N]) -> Self;
+    fn store(self) -> [u32; N];
+    fn add(self, other : Self) -> Self;
+    fn and(self, other : Self) -> Self;
+    fn or(self, other : Self) -> Self;
+    fn xor(self, other : Self) -> Self;
+    // !self & other
+    fn and_not(self, other : Self) -> Self;
+    fn rotate_left(self, bits : u32) -> Self;
+}
+
+#[derive(Clone, Copy)]
+struct Portable<const N : usize>([u32; N]);
+
+impl<const N : usize> Portable<N> {
+    #[inline(always)]
+    fn zip(self, other : Portable<N>, f : fn(u32, u32) -> u32) -> Portable<N> {
+        let mut words = self.0;
+        for (word, other) in words.iter_mut().zip(other.0.iter()) {
+            *word = f(*word, *other);
+        }
+        Portable(words)
+    }
+}
+
+impl<const N : usize> Lanes<N> for Portable<N> {
+    #[inline(always)]
+    fn splat(word : u32) -> Self { Portable([word; N]) }
+    #[inline(always)]
+    fn load(words : [u32; N]) -> Self { Portable(words) }
+    #[inline(always)]
+    fn store(self) -> [u32; N] { self.0 }
+    #[inline(always)]
+    fn add(self, other : Self) -> Self { self.zip(other, u32::wrapping_add) }
+    #[inline(always)]
+    fn and(self, other : Self) -> Self { self.zip(other, |a, b| a & b) }
+    #[inline(always)]
+    fn or(self, other : Self) -> Self { self.zip(other, |a, b| a | b) }
+    #[inline(always)]
+    fn xor(self, other : Self) -> Self { self.zip(other, |a, b| a ^ b) }
+    #[inline(always)]
+    fn and_not(self, other : Self) -> Self { self.zip(other, |a, b| !a & b) }
+    #[inline(always)]
+    fn rotate_left(self, bits : u32) -> Self { Portable(self.0.map(|w| w.rotate_left(bits))) }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use md5::FinalBlocks;
+    use md5_lanes::{ finalize_lanes, Lanes };
+    use std::arch::x86_64::*;
+
+    #[derive(Clone, Copy)]
+    pub struct Sse2(__m128i);
+
+    impl Lanes<4> for Sse2 {
+        #[inline(always)]
+        fn splat(word : u32) -> Self { Sse2(unsafe { _mm_set1_epi32(word as i32) }) }
+        #[inline(always)]
+        fn load(words : [u32; 4]) -> Self { Sse2(unsafe { _mm_loadu_si128(words.as_ptr() as *const __m128i) }) }
+        #[inline(always)]
+        fn store(self) -> [u32; 4] {
+            let mut words = [0u32; 4];
+            unsafe { _mm_storeu_si128(words.as_mut_ptr() as *mut __m128i, self.0) };
+            words
+        }
+        #[inline(always)]
+        fn add(self, other : Self) -> Self { Sse2(unsafe { _mm_add_epi32(self.0, other.0) }) }
+        #[inline(always)]
+        fn and(self, other : Self) -> Self { Sse2(unsafe { _mm_and_si128(self.0, other.0) }) }
+        #[inline(always)]
+        fn or(self, other : Self) -> Self { Sse2(unsafe { _mm_or_si128(self.0, other.0) }) }
+        #[inline(always)]
+        fn xor(self, other : Self) -> Self { Sse2(unsafe { _mm_xor_si128(self.0, other.0) }) }
+        #[inline(always)]
+        fn and_not(self, other : Self) -> Self { Sse2(unsafe { _mm_andnot_si128(self.0, other.0) }) }
+        #[inline(always)]
+        fn rotate_left(self, bits : u32) -> Self {
+            unsafe {
+                let left = _mm_sll_epi32(self.0, _mm_cvtsi32_si128(bits as i32));
+                let right = _mm_srl_epi32(self.0, _mm_cvtsi32_si128(32 - bits as i32));
+                Sse2(_mm_or_si128(left, right))
+            }
+        }
+    }
+
+    // Only ever called from inside `finalize_avx2`, which has avx2 enabled,
+    // so every intrinsic ends up inlined there.
+    #[derive(Clone, Copy)]
+    pub struct Avx2(__m256i);
+
+    impl Lanes<8> for Avx2 {
+        #[inline(always)]
+        fn splat(word : u32) -> Self { Avx2(unsafe { _mm256_set1_epi32(word as i32) }) }
+        #[inline(always)]
+        fn load(words : [u32; 8]) -> Self { Avx2(unsafe { _mm256_loadu_si256(words.as_ptr() as *const __m256i) }) }
+        #[inline(always)]
+        fn store(self) -> [u32; 8] {
+            let mut words = [0u32; 8];
+            unsafe { _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, self.0) };
+            words
+        }
+        #[inline(always)]
+        fn add(self, other : Self) -> Self { Avx2(unsafe { _mm256_add_epi32(self.0, other.0) }) }
+        #[inline(always)]
+        fn and(self, other : Self) -> Self { Avx2(unsafe { _mm256_and_si256(self.0, other.0) }) }
+        #[inline(always)]
+        fn or(self, other : Self) -> Self { Avx2(unsafe { _mm256_or_si256(self.0, other.0) }) }
+        #[inline(always)]
+        fn xor(self, other : Self) -> Self { Avx2(unsafe { _mm256_xor_si256(self.0, other.0) }) }
+        #[inline(always)]
+        fn and_not(self, other : Self) -> Self { Avx2(unsafe { _mm256_andnot_si256(self.0, other.0) }) }
+        #[inline(always)]
+        fn rotate_left(self, bits : u32) -> Self {
+            unsafe {
+                let left = _mm256_sll_epi32(self.0, _mm_cvtsi32_si128(bits as i32));
+                let right = _mm256_srl_epi32(self.0, _mm_cvtsi32_si128(32 - bits as i32));
+                Avx2(_mm256_or_si256(left, right))
+            }
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn finalize_avx2(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+        finalize_lanes::<Avx2, 8>(finals, digests)
+    }
+}
+
+// runs the final blocks of exactly N messages, which all have the same number of them
+#[inline(always)]
+fn finalize_lanes<L: Lanes<N>, const N : usize>(finals : &[FinalBlocks], digests : &mut [[u8; 16]]) {
+    let mut state = [L::splat(0); 4];
+    for (word, lanes) in state.iter_mut().enumerate() {
+        *lanes = L::load(std::array::from_fn(|lane| finals[lane].state[word]));
+    }
+
+    for block in 0..finals[0].count {
+        let mut m = [L::splat(0); 16];
+        for (word, lanes) in m.iter_mut().enumerate() {
+            *lanes = L::load(std::array::from_fn(|lane| {
+                let bytes = &finals[lane].blocks[block][word * 4..word * 4 + 4];
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            }));
+        }
+        compress(&mut state, &m);
+    }
+
+    let words = state.map(|lanes| lanes.store());
+    for (lane, digest) in digests.iter_mut().enumerate().take(N) {
+        *digest = to_digest(&[words[0][lane], words[1][lane], words[2][lane], words[3][lane]]);
+    }
+}
+
+// `md5::compress`, on vectors
+#[inline(always)]
+fn compress<L: Lanes<N>, const N : usize>(state : &mut [L; 4], m : &[L; 16]) {
+    let [mut a, mut b, mut c, mut d] = *state;
+    let ones = L::splat(!0);
+
+    macro_rules! round {
+        ($range:expr, $f:expr, $g:expr) => {
+            for i in $range {
+                let f : L = $f(b, c, d);
+                let rotated = a.add(f).add(L::splat(K[i])).add(m[$g(i)]).rotate_left(S[i]);
+                a = d;
+                d = c;
+                c = b;
+                b = b.add(rotated);
+            }
+        };
+    }
+
+    round!(0..16, |b: L, c: L, d: L| b.and(c).or(b.and_not(d)), |i: usize| i);
+    round!(16..32, |b: L, c: L, d: L| d.and(b).or(d.and_not(c)), |i: usize| (5 * i + 1) % 16);
+    round!(32..48, |b: L, c: L, d: L| b.xor(c).xor(d), |i: usize| (3 * i + 5) % 16);
+    round!(48..64, |b: L, c: L, d: L| c.xor(b.or(d.xor(ones))), |i: usize| (7 * i) % 16);
+
+    state[0] = state[0].add(a);
+    state[1] = state[1].add(b);
+    state[2] = state[2].add(c);
+    state[3] = state[3].add(d);
+}
+
+#[cfg(test)]
+mod lanes {
+    use md5::Md5;
+    use md5_lanes::{ finalize, Backend, MAX_LANES };
+
+    fn hashers(messages : &[String]) -> Vec<Md5> {
+        messages.iter().map(|message| {
+            let mut md5 = Md5::new();
+            md5.update(message.as_bytes());
+            md5
+        }).collect()
+    }
+
+    fn check(backend : Backend, messages : &[String]) {
+        let hashers = hashers(messages);
+        let mut digests = vec![[0u8; 16]; hashers.len()];
+        finalize(backend, &hashers, &mut digests);
+
+        for (hasher, digest) in hashers.iter().zip(digests.iter()) {
+            assert_eq!(*digest, hasher.finalize(), "{} backend", backend);
+        }
+    }
+
+    fn backends() -> Vec<Backend> {
+        [Backend::Portable, Backend::Sse2, Backend::Avx2].iter().cloned().filter(|b| b.is_supported()).collect()
+    }
+
+    #[test]
+    fn it_matches_the_scalar_digests() {
+        let messages : Vec<String> = (0..100).map(|i| format!("abc{}", i * 7919)).collect();
+        for backend in backends() {
+            check(backend, &messages);
+        }
+    }
+
+    #[test]
+    fn it_handles_uneven_padding() {
+        // 55 bytes pad into one block and 56 need two, mixing both falls back to scalar
+        let messages : Vec<String> = (40..80).map(|length| "a".repeat(length)).collect();
+        for backend in backends() {
+            check(backend, &messages);
+            check(backend, &messages[..MAX_LANES + 3]);
+        }
+    }
+
+    #[test]
+    fn it_only_detects_supported_backends() {
+        assert!(Backend::detect().is_supported());
+        assert!(Backend::Portable.is_supported());
+        assert_eq!("portable".parse(), Ok(Backend::Portable));
+        assert!("neon".parse::<Backend>().is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+use std::time::{ Duration, Instant };
+
+// Measures how fast a search is going. Indices from before a resume don't
+// count towards the speed, but the hits found before it still tell how far
+// apart hits are, which is what the estimate is based on.
+pub struct Progress {
+    started: Instant,
+    first_index: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub searched: u64,
+    pub hashes_per_second: f64,
+    // `None` until there is anything to base an estimate on
+    pub remaining: Option<Duration>,
+}
+
+impl Progress {
+    pub fn new(first_index : u64) -> Progress {
+        Progress { started: Instant::now(), first_index }
+    }
+
+    // `searched` indices are done, they produced `hits` hits, and `hits_left` more are expected to be needed
+    pub fn report(&self, searched : u64, hits : usize, hits_left : f64) -> Report {
+        estimate(searched, searched.saturating_sub(self.first_index), self.started.elapsed(), hits, hits_left)
+    }
+}
+
+fn estimate(searched : u64, hashed : u64, elapsed : Duration, hits : usize, hits_left : f64) -> Report {
+    let seconds = elapsed.as_secs_f64();
+    let hashes_per_second = if seconds > 0.0 { hashed as f64 / seconds } else { 0.0 };
+
+    let remaining = if hits > 0 && hashes_per_second > 0.0 {
+        let indices_per_hit = searched as f64 / hits as f64;
+        Some(Duration::from_secs_f64(hits_left * indices_per_hit / hashes_per_second))
+    } else {
+        None
+    };
+
+    Report { searched, hashes_per_second, remaining }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "searched {} indices, {:.2}M hashes/s", self.searched, self.hashes_per_second / 1e6)?;
+        match self.remaining {
+            Some(remaining) => {
+                let seconds = remaining.as_secs();
+                write!(f, ", about {}m{:02}s left", seconds / 60, seconds % 60)
+            }
+            None => write!(f, ", no estimate yet"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod estimate {
+    use progress::estimate;
+    use std::time::Duration;
+
+    #[test]
+    fn it_estimates_the_time_left() {
+        // 2M indices in 2 seconds, 4 hits so far: 500k indices per hit, so 4 more take 2 seconds
+        let report = estimate(2_000_000, 2_000_000, Duration::from_secs(2), 4, 4.0);
+        assert_eq!(report.hashes_per_second, 1_000_000.0);
+        assert_eq!(report.remaining, Some(Duration::from_secs(2)));
+        assert_eq!(report.to_string(), "searched 2000000 indices, 1.00M hashes/s, about 0m02s left");
+    }
+
+    #[test]
+    fn it_only_counts_hashes_since_resuming() {
+        let report = estimate(2_000_000, 500_000, Duration::from_secs(1), 4, 2.0);
+        assert_eq!(report.hashes_per_second, 500_000.0);
+        assert_eq!(report.remaining, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn it_waits_for_a_first_hit() {
+        assert_eq!(estimate(1000, 1000, Duration::from_secs(1), 0, 8.0).remaining, None);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{ BTreeMap, VecDeque };
+use std::iter;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
+use std::sync::mpsc::{ sync_channel, Receiver };
+use std::thread;
+
+// an index whose hash starts with enough zeroes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub index: u64,
+    pub hash: String,
+}
+
+// What a search has to say: a hit, or that it checked every index below
+// some index. Those come after every batch, so there is news between hits too.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Hit(Hit),
+    Searched(u64),
+}
+
+impl Event {
+    // for searches that only care about the hits, `.filter_map(Event::hit)`
+    pub fn hit(self) -> Option<Hit> {
+        match self {
+            Event::Hit(hit) => Some(hit),
+            Event::Searched(_) => None,
+        }
+    }
+}
+
+// Checks one index at a time from `start` on, on the current thread. Reports
+// progress after every `batch_size` indices, just like `ParallelSearch` does.
+pub fn sequential<C>(start: u64, batch_size: u64, mut check: C) -> impl Iterator<Item = Event>
+    where C: FnMut(u64) -> Option<Hit>
+{
+    let batch_size = batch_size.max(1);
+    (0..)
+        .map(move |batch: u64| batch.saturating_mul(batch_size).saturating_add(start))
+        .take_while(|&from| from < u64::MAX)
+        .flat_map(move |from| {
+            let to = from.saturating_add(batch_size);
+            let hits : Vec<Event> = (from..to).filter_map(&mut check).map(Event::Hit).collect();
+            hits.into_iter().chain(iter::once(Event::Searched(to)))
+        })
+}
+
+// Hits found by worker threads. Every worker claims the next batch of
+// indices, and batches are handed out in order again, so iterating yields
+// exactly the same events as `sequential` does. Dropping the search stops the workers.
+pub struct ParallelSearch {
+    receiver: Receiver<(u64, Vec<Hit>)>,
+    // batches that finished before the batches in front of them
+    finished: BTreeMap<u64, Vec<Hit>>,
+    next_batch: u64,
+    ready: VecDeque<Event>,
+    stop: Arc<AtomicBool>,
+    start: u64,
+    batch_size: u64,
+}
+
+impl ParallelSearch {
+    // `make_check` is called once per worker, so checks can keep reusable state around.
+    // The search covers the indices from `start` on.
+    pub fn new<F, C>(threads: usize, batch_size: u64, start: u64, make_check: F) -> ParallelSearch
+        where F: Fn() -> C + Send + Sync + 'static,
+              C: FnMut(u64) -> Option<Hit>
+    {
+        let threads = threads.max(1);
+        let batch_size = batch_size.max(1);
+
+        let make_check = Arc::new(make_check);
+        let next = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // a bounded channel keeps the workers from running too far ahead
+        let (sender, receiver) = sync_channel(threads * 4);
+
+        for _ in 0..threads {
+            let make_check = make_check.clone();
+            let next = next.clone();
+            let stop = stop.clone();
+            let sender = sender.clone();
+
+            thread::spawn(move || {
+                let mut check = make_check();
+                while !stop.load(Ordering::Relaxed) {
+                    let batch = next.fetch_add(1, Ordering::Relaxed);
+                    let from = batch.saturating_mul(batch_size).saturating_add(start);
+                    if from == u64::MAX {
+                        break;
+                    }
+
+                    let to = from.saturating_add(batch_size);
+                    let hits : Vec<Hit> = (from..to).filter_map(&mut check).collect();
+
+                    // receiver is gone, nobody cares about hits anymore
+                    if sender.send((batch, hits)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        ParallelSearch {
+            receiver,
+            finished: BTreeMap::new(),
+            next_batch: 0,
+            ready: VecDeque::new(),
+            stop,
+            start,
+            batch_size,
+        }
+    }
+}
+
+impl Iterator for ParallelSearch {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(event);
+            }
+
+            // next batch in line already arrived?
+            if let Some(hits) = self.finished.remove(&self.next_batch) {
+                self.next_batch += 1;
+                self.ready.extend(hits.into_iter().map(Event::Hit));
+
+                let searched = self.next_batch.saturating_mul(self.batch_size).saturating_add(self.start);
+                self.ready.push_back(Event::Searched(searched));
+                continue;
+            }
+
+            match self.receiver.recv() {
+                Ok((batch, hits)) => { self.finished.insert(batch, hits); }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+// workers notice the stop flag, or the dropped receiver, once their current batch is done
+impl Drop for ParallelSearch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod parallel_search {
+    use search::{ sequential, Event, Hit, ParallelSearch };
+
+    fn check(index: u64) -> Option<Hit> {
+        // something cheap, but irregular enough to end up unevenly across batches
+        if (index * 2654435761) % 97 < 3 {
+            Some(Hit { index, hash: format!("{:x}", index) })
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn it_yields_the_same_hits_as_sequential() {
+        let expected : Vec<Hit> = sequential(0, 1, check).filter_map(Event::hit).take(500).collect();
+
+        for &(threads, batch_size) in &[(1, 1), (4, 7), (8, 1000)] {
+            let hits : Vec<Hit> = ParallelSearch::new(threads, batch_size, 0, || check).filter_map(Event::hit).take(500).collect();
+            assert_eq!(hits, expected);
+
+            let events : Vec<Event> = sequential(0, batch_size, check).take(200).collect();
+            assert_eq!(ParallelSearch::new(threads, batch_size, 0, || check).take(200).collect::<Vec<Event>>(), events);
+        }
+    }
+
+    #[test]
+    fn it_resumes_from_a_start_index() {
+        let all : Vec<Hit> = sequential(0, 10, check).filter_map(Event::hit).take(100).collect();
+        let start = all[49].index + 1;
+
+        assert_eq!(sequential(start, 10, check).filter_map(Event::hit).take(50).collect::<Vec<Hit>>(), &all[50..]);
+        assert_eq!(ParallelSearch::new(4, 7, start, || check).filter_map(Event::hit).take(50).collect::<Vec<Hit>>(), &all[50..]);
+    }
+
+    #[test]
+    fn it_yields_hits_in_ascending_order() {
+        let hits : Vec<Hit> = ParallelSearch::new(4, 3, 0, || check).filter_map(Event::hit).take(1000).collect();
+        assert!(hits.windows(2).all(|w| w[0].index < w[1].index));
+    }
+
+    #[test]
+    fn it_reports_progress_between_hits() {
+        // every index below `searched` is done, so hits after it are above it
+        let mut searched = 5;
+        for event in ParallelSearch::new(4, 10, 5, || check).take(300) {
+            match event {
+                Event::Hit(hit) => assert!(hit.index >= searched),
+                Event::Searched(next) => {
+                    assert_eq!(next, searched + 10);
+                    searched = next;
+                }
+            }
+        }
+        assert!(searched > 5);
+    }
+}
@@ -0,0 +1,91 @@
+// SHA-1 as described in FIPS 180-4, with the same streaming api as `Md5`
+
+use block::{ BlockBuffer, Endian };
+
+const INITIAL_STATE : [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sha1 {
+    state: [u32; 5],
+    blocks: BlockBuffer,
+}
+
+impl Default for Sha1 {
+    fn default() -> Sha1 {
+        Sha1::new()
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Sha1 {
+        Sha1 { state: INITIAL_STATE, blocks: BlockBuffer::new() }
+    }
+
+    pub fn update(&mut self, input : &[u8]) {
+        let state = &mut self.state;
+        self.blocks.update(input, |block| compress(state, block));
+    }
+
+    pub fn finalize(mut self) -> [u8; 20] {
+        let state = &mut self.state;
+        self.blocks.finalize(Endian::Big, |block| compress(state, block));
+
+        let mut digest = [0u8; 20];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+fn compress(state : &mut [u32; 5], block : &[u8]) {
+    let mut w = [0u32; 80];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (i, word) in w.iter().enumerate() {
+        let (f, k) = match i / 20 {
+            0 => ((b & c) | (!b & d), 0x5a827999),
+            1 => (b ^ c ^ d, 0x6ed9eba1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+
+        let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e].iter()) {
+        *s = s.wrapping_add(*v);
+    }
+}
+
+#[cfg(test)]
+mod fips_180 {
+    use sha1::Sha1;
+    use hasher::to_hex;
+
+    fn sha1(input : &[u8]) -> String {
+        let mut sha1 = Sha1::new();
+        sha1.update(input);
+        to_hex(&sha1.finalize())
+    }
+
+    #[test]
+    fn it_hashes_the_test_vectors() {
+        assert_eq!(sha1(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(sha1(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+        assert_eq!(sha1(&vec![b'a'; 1_000_000]), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+// how often every grapheme occurs in one column. Graphemes rather than chars,
+// so an accented letter written as letter + combining accent is one symbol.
+pub type Column = HashMap<String, usize>;
+
+// Counts per column for any number of lines. Lines don't need to be the same
+// length: columns are added as soon as a line reaches them, and a short line
+// simply doesn't count towards the columns it doesn't reach.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnFrequencies {
+    columns: Vec<Column>,
+    // per column, the line every grapheme first showed up on
+    first_seen: Vec<HashMap<String, usize>>,
+    lines: usize,
+}
+
+impl ColumnFrequencies {
+    pub fn new() -> ColumnFrequencies {
+        ColumnFrequencies::default()
+    }
+
+    pub fn from_text(text : &str) -> ColumnFrequencies {
+        let mut frequencies = ColumnFrequencies::new();
+        for line in text.lines() {
+            frequencies.add_line(line);
+        }
+        frequencies
+    }
+
+    // a trailing CR or LF is not part of the line, blank lines are skipped
+    pub fn add_line(&mut self, line : &str) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return;
+        }
+
+        for (idx, grapheme) in line.graphemes(true).enumerate() {
+            if idx == self.columns.len() {
+                self.columns.push(Column::new());
+                self.first_seen.push(HashMap::new());
+            }
+
+            *self.columns[idx].entry(grapheme.to_string()).or_insert(0) += 1;
+            self.first_seen[idx].entry(grapheme.to_string()).or_insert(self.lines);
+        }
+
+        self.lines += 1;
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    // line number every grapheme of column `idx` was first seen on
+    pub fn first_seen(&self, idx : usize) -> &HashMap<String, usize> {
+        &self.first_seen[idx]
+    }
+
+    // number of lines that were counted
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod frequencies {
+    use column_frequencies::ColumnFrequencies;
+
+    #[test]
+    fn it_counts_per_column() {
+        let frequencies = ColumnFrequencies::from_text("ab\nac\nbc\n");
+        let columns = frequencies.columns();
+
+        assert_eq!(frequencies.lines(), 3);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0]["a"], 2);
+        assert_eq!(columns[0]["b"], 1);
+        assert_eq!(columns[1]["c"], 2);
+    }
+
+    #[test]
+    fn it_grows_columns_for_ragged_lines() {
+        let frequencies = ColumnFrequencies::from_text("a\nabc\n\nab");
+        let columns = frequencies.columns();
+
+        assert_eq!(frequencies.lines(), 3);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0]["a"], 3);
+        assert_eq!(columns[1]["b"], 2);
+        assert_eq!(columns[2]["c"], 1);
+    }
+
+    #[test]
+    fn it_remembers_where_graphemes_first_showed_up() {
+        let frequencies = ColumnFrequencies::from_text("ab\nbb\n\nca");
+        assert_eq!(frequencies.first_seen(0)["a"], 0);
+        assert_eq!(frequencies.first_seen(0)["b"], 1);
+        assert_eq!(frequencies.first_seen(0)["c"], 2);
+        assert_eq!(frequencies.first_seen(1)["a"], 2);
+    }
+
+    #[test]
+    fn it_ignores_line_endings() {
+        let mut frequencies = ColumnFrequencies::from_text("ab\r\nab\r\n");
+        frequencies.add_line("ab\r\n");
+
+        assert_eq!(frequencies.lines(), 3);
+        assert_eq!(frequencies.columns().len(), 2);
+        assert_eq!(frequencies.columns()[1]["b"], 3);
+    }
+
+    #[test]
+    fn it_counts_grapheme_clusters() {
+        // e + combining acute accent, and a flag made of two regional indicators
+        let frequencies = ColumnFrequencies::from_text("e\u{301}🇳🇱x\ne\u{301}🇳🇱y");
+        let columns = frequencies.columns();
+
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0]["e\u{301}"], 2);
+        assert_eq!(columns[1]["🇳🇱"], 2);
+        assert_eq!(columns[2]["y"], 1);
+    }
+}
@@ -0,0 +1,145 @@
+use column_frequencies::ColumnFrequencies;
+use decoder::{ sort_column, Pick, TieBreak };
+use std::fmt;
+
+// how sure a single column is about its pick
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnConfidence {
+    pub column: usize,
+    pub winner: String,
+    // the winner's part of all graphemes in the column
+    pub share: f64,
+    pub runner_up: Option<String>,
+    // difference with the runner-up's share, 1.0 when there is no runner-up
+    pub margin: f64,
+}
+
+impl fmt::Display for ColumnConfidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {} with {:.1}%", self.column, self.winner, self.share * 100.0)?;
+        match self.runner_up {
+            Some(ref runner_up) => write!(f, ", {:.1} points ahead of {}", self.margin * 100.0, runner_up),
+            None => write!(f, ", no runner-up"),
+        }
+    }
+}
+
+// a whole message and how likely the columns make it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub message: String,
+    // sum of the log of every grapheme's share in its column
+    pub log_likelihood: f64,
+}
+
+// the columns' shares, in the order `sort_column` picks them
+pub fn shares(frequencies : &ColumnFrequencies, pick : Pick, policy : TieBreak) -> Vec<Vec<(String, f64)>> {
+    frequencies.columns().iter().enumerate()
+        .map(|(idx, column)| {
+            let total = column.values().sum::<usize>() as f64;
+            sort_column(column, frequencies.first_seen(idx), pick, policy).into_iter()
+                .map(|g| (g.to_string(), column[g] as f64 / total))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn column_confidence(frequencies : &ColumnFrequencies, pick : Pick, policy : TieBreak) -> Vec<ColumnConfidence> {
+    shares(frequencies, pick, policy).into_iter().enumerate()
+        .map(|(column, shares)| {
+            let (ref winner, share) = shares[0];
+            let runner_up = shares.get(1);
+
+            ColumnConfidence {
+                column,
+                winner: winner.to_string(),
+                share,
+                runner_up: runner_up.map(|r| r.0.to_string()),
+                margin: runner_up.map_or(1.0, |r| (share - r.1).abs()),
+            }
+        })
+        .collect()
+}
+
+// The `k` best messages. For the most common pick those are the most likely
+// ones, for the least common pick the least likely ones. Any of the k best
+// messages starts with one of the k best prefixes, so keeping k prefixes per
+// column is exact.
+pub fn top_messages(frequencies : &ColumnFrequencies, pick : Pick, policy : TieBreak, k : usize) -> Vec<Candidate> {
+    let mut candidates = vec![Candidate { message: String::new(), log_likelihood: 0.0 }];
+
+    for column in shares(frequencies, pick, policy) {
+        let mut extended : Vec<Candidate> = candidates.iter()
+            .flat_map(|prefix| column.iter().map(move |&(ref grapheme, share)| Candidate {
+                message: format!("{}{}", prefix.message, grapheme),
+                log_likelihood: prefix.log_likelihood + share.ln(),
+            }))
+            .collect();
+
+        // stable, so equally likely messages keep the tie-break order
+        extended.sort_by(|a, b| match pick {
+            Pick::MostCommon => b.log_likelihood.total_cmp(&a.log_likelihood),
+            Pick::LeastCommon => a.log_likelihood.total_cmp(&b.log_likelihood),
+        });
+        extended.truncate(k);
+        candidates = extended;
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod scores {
+    use column_frequencies::ColumnFrequencies;
+    use confidence::{ column_confidence, top_messages };
+    use decoder::{ Pick, TieBreak };
+
+    #[test]
+    fn it_scores_every_column() {
+        let frequencies = ColumnFrequencies::from_text("ax\nay\nay\nbx");
+        let confidence = column_confidence(&frequencies, Pick::MostCommon, TieBreak::Alphabetical);
+
+        assert_eq!(confidence[0].winner, "a");
+        assert_eq!(confidence[0].share, 0.75);
+        assert_eq!(confidence[0].runner_up, Some("b".to_string()));
+        assert_eq!(confidence[0].margin, 0.5);
+
+        // x and y tie, alphabetical order puts x first
+        assert_eq!(confidence[1].winner, "x");
+        assert_eq!(confidence[1].margin, 0.0);
+        assert_eq!(confidence[1].to_string(), "column 1: x with 50.0%, 0.0 points ahead of y");
+    }
+
+    #[test]
+    fn it_has_no_runner_up_for_unanimous_columns() {
+        let frequencies = ColumnFrequencies::from_text("a\na");
+        let confidence = column_confidence(&frequencies, Pick::LeastCommon, TieBreak::Alphabetical);
+        assert_eq!(confidence[0].runner_up, None);
+        assert_eq!(confidence[0].margin, 1.0);
+    }
+
+    #[test]
+    fn it_ranks_messages_by_likelihood() {
+        // column 0: a 3/4, b 1/4. column 1: x 2/4, y 1/4, z 1/4
+        let frequencies = ColumnFrequencies::from_text("ax\nax\nay\nbz");
+
+        let most : Vec<String> = top_messages(&frequencies, Pick::MostCommon, TieBreak::Alphabetical, 3)
+            .into_iter().map(|c| c.message).collect();
+        assert_eq!(most, vec!["ax", "ay", "az"]);
+
+        let least = top_messages(&frequencies, Pick::LeastCommon, TieBreak::Alphabetical, 2);
+        assert_eq!(least[0].message, "by");
+        assert_eq!(least[1].message, "bz");
+        assert!((least[0].log_likelihood - (0.25f64.ln() * 2.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_puts_the_decoded_message_first() {
+        let example = "eedadn\ndrvtee\neandsr\nraavrd\natevrs\ntsrnev\nsdttsa\nrasrtv\nnssdts\nntnada\nsvetve\ntesnvt\nvntsnd\nvrdear\ndvrsen\nenarar\n";
+        let frequencies = ColumnFrequencies::from_text(example);
+
+        assert_eq!(top_messages(&frequencies, Pick::MostCommon, TieBreak::Alphabetical, 5)[0].message, "easter");
+        assert_eq!(top_messages(&frequencies, Pick::LeastCommon, TieBreak::Alphabetical, 5)[0].message, "advent");
+        assert_eq!(top_messages(&frequencies, Pick::MostCommon, TieBreak::Alphabetical, 5).len(), 5);
+    }
+}
@@ -0,0 +1,158 @@
+use column_frequencies::{ Column, ColumnFrequencies };
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+// which grapheme of a column makes it into the message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pick {
+    MostCommon,
+    LeastCommon,
+}
+
+// what to do when several graphemes are equally common
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    Alphabetical,
+    FirstSeen,
+    // refuse to pick, the message can't be trusted
+    Error,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alphabetical" => Ok(TieBreak::Alphabetical),
+            "first-seen" => Ok(TieBreak::FirstSeen),
+            "error" => Ok(TieBreak::Error),
+            _ => Err(format!("unknown tie-break policy '{}', expected alphabetical, first-seen or error", s)),
+        }
+    }
+}
+
+// a column where the pick had to be made between equally common graphemes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambiguity {
+    pub column: usize,
+    pub count: usize,
+    // in alphabetical order
+    pub candidates: Vec<String>,
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {} all occur {} times", self.column, self.candidates.join(", "), self.count)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub message: String,
+    // ties that were broken by the policy
+    pub ambiguous: Vec<Ambiguity>,
+}
+
+// Graphemes of a column, the one to pick first. Ties are ordered by the
+// policy, `Error` orders them alphabetically so the order is still stable.
+pub fn sort_column<'a>(column : &'a Column, first_seen : &HashMap<String, usize>, pick : Pick, policy : TieBreak) -> Vec<&'a String> {
+    let mut thingy : Vec<(&String, &usize)> = column.iter().collect();
+    thingy.sort_by(|a, b| {
+        let by_count = match pick {
+            Pick::MostCommon => b.1.cmp(a.1),
+            Pick::LeastCommon => a.1.cmp(b.1),
+        };
+
+        let by_policy = match policy {
+            TieBreak::FirstSeen => first_seen.get(a.0).cmp(&first_seen.get(b.0)),
+            TieBreak::Alphabetical | TieBreak::Error => a.0.cmp(b.0),
+        };
+
+        by_count.then(by_policy)
+    });
+    thingy.iter().map(|r| r.0).collect()
+}
+
+// graphemes that are as common as the one that gets picked, if there is more than one
+fn ambiguity(idx : usize, column : &Column, sorted : &[&String]) -> Option<Ambiguity> {
+    let count = column[sorted[0]];
+    let mut candidates : Vec<String> = sorted.iter()
+        .take_while(|g| column[**g] == count)
+        .map(|g| g.to_string())
+        .collect();
+
+    if candidates.len() < 2 {
+        return None;
+    }
+
+    candidates.sort();
+    Some(Ambiguity { column: idx, count, candidates })
+}
+
+// the message, or with `TieBreak::Error` every column that couldn't be decided
+pub fn decode(frequencies : &ColumnFrequencies, pick : Pick, policy : TieBreak) -> Result<Decoded, Vec<Ambiguity>> {
+    let mut message = String::new();
+    let mut ambiguous = Vec::new();
+
+    for (idx, column) in frequencies.columns().iter().enumerate() {
+        let sorted = sort_column(column, frequencies.first_seen(idx), pick, policy);
+        message.push_str(sorted[0]);
+        ambiguous.extend(ambiguity(idx, column, &sorted));
+    }
+
+    if policy == TieBreak::Error && !ambiguous.is_empty() {
+        return Err(ambiguous);
+    }
+
+    Ok(Decoded { message, ambiguous })
+}
+
+#[cfg(test)]
+mod decode {
+    use column_frequencies::ColumnFrequencies;
+    use decoder::{ decode, Ambiguity, Pick, TieBreak };
+
+    const EXAMPLE : &str = "eedadn\ndrvtee\neandsr\nraavrd\natevrs\ntsrnev\nsdttsa\nrasrtv\nnssdts\nntnada\nsvetve\ntesnvt\nvntsnd\nvrdear\ndvrsen\nenarar\n";
+
+    #[test]
+    fn it_decodes_the_example() {
+        let frequencies = ColumnFrequencies::from_text(EXAMPLE);
+        for &policy in &[TieBreak::Alphabetical, TieBreak::FirstSeen, TieBreak::Error] {
+            assert_eq!(decode(&frequencies, Pick::MostCommon, policy).unwrap().message, "easter");
+            assert_eq!(decode(&frequencies, Pick::LeastCommon, policy).unwrap().message, "advent");
+        }
+    }
+
+    #[test]
+    fn it_breaks_ties_by_policy() {
+        // column 0 has b and a twice each, column 1 has a single z
+        let frequencies = ColumnFrequencies::from_text("bz\na\nb\na\nc");
+
+        let alphabetical = decode(&frequencies, Pick::MostCommon, TieBreak::Alphabetical).unwrap();
+        assert_eq!(alphabetical.message, "az");
+        assert_eq!(alphabetical.ambiguous, vec![Ambiguity { column: 0, count: 2, candidates: vec!["a".to_string(), "b".to_string()] }]);
+
+        let first_seen = decode(&frequencies, Pick::MostCommon, TieBreak::FirstSeen).unwrap();
+        assert_eq!(first_seen.message, "bz");
+
+        // only c is the least common, no tie there
+        let least = decode(&frequencies, Pick::LeastCommon, TieBreak::Error).unwrap();
+        assert_eq!(least.message, "cz");
+        assert!(least.ambiguous.is_empty());
+    }
+
+    #[test]
+    fn it_reports_every_ambiguous_column() {
+        let frequencies = ColumnFrequencies::from_text("ab\nba");
+        let ambiguous = decode(&frequencies, Pick::MostCommon, TieBreak::Error).unwrap_err();
+
+        assert_eq!(ambiguous.len(), 2);
+        assert_eq!(ambiguous[1].to_string(), "column 1: a, b all occur 1 times");
+    }
+
+    #[test]
+    fn it_parses_policies() {
+        assert_eq!("first-seen".parse(), Ok(TieBreak::FirstSeen));
+        assert!("random".parse::<TieBreak>().is_err());
+    }
+}
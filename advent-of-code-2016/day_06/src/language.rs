@@ -0,0 +1,177 @@
+use column_frequencies::ColumnFrequencies;
+use confidence::shares;
+use decoder::{ Pick, TieBreak };
+use std::collections::{ HashMap, HashSet };
+use unicode_segmentation::UnicodeSegmentation;
+
+// a whole message read with the help of a language
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub message: String,
+    // higher is better, combines the columns and the language
+    pub score: f64,
+}
+
+// How well a grapheme fits its column. The most common pick rewards likely
+// graphemes and the least common pick unlikely ones, graphemes that never
+// showed up in the column can't be picked at all.
+fn column_scores(frequencies : &ColumnFrequencies, pick : Pick) -> Vec<HashMap<String, f64>> {
+    shares(frequencies, pick, TieBreak::Alphabetical).into_iter()
+        .map(|column| column.into_iter()
+            .map(|(grapheme, share)| match pick {
+                Pick::MostCommon => (grapheme, share.ln()),
+                Pick::LeastCommon => (grapheme, -share.ln()),
+            })
+            .collect())
+        .collect()
+}
+
+// The word from the list that fits the columns best. Only words with exactly
+// one grapheme per column are considered.
+pub fn best_word(frequencies : &ColumnFrequencies, pick : Pick, words : &[String]) -> Option<Reading> {
+    let scores = column_scores(frequencies, pick);
+
+    let mut best : Option<Reading> = None;
+    for word in words {
+        let graphemes : Vec<&str> = word.graphemes(true).collect();
+        if graphemes.len() != scores.len() {
+            continue;
+        }
+
+        let score : Option<f64> = graphemes.iter().zip(scores.iter())
+            .map(|(g, column)| column.get(*g).cloned())
+            .sum();
+
+        if let Some(score) = score {
+            if best.as_ref().is_none_or(|b| score > b.score) {
+                best = Some(Reading { message: word.to_string(), score });
+            }
+        }
+    }
+
+    best
+}
+
+// Character n-grams counted from a sample of readable text, with add-one
+// smoothing so unseen n-grams are unlikely rather than impossible.
+#[derive(Debug, Clone)]
+pub struct NGramModel {
+    order: usize,
+    // n-gram -> count, and its first n - 1 graphemes -> count
+    ngrams: HashMap<String, usize>,
+    contexts: HashMap<String, usize>,
+    alphabet: usize,
+}
+
+impl NGramModel {
+    pub fn train(text : &str, order : usize) -> NGramModel {
+        let order = order.max(1);
+        let mut ngrams = HashMap::new();
+        let mut contexts = HashMap::new();
+        let mut alphabet = HashSet::new();
+
+        for line in text.lines() {
+            let graphemes : Vec<&str> = line.graphemes(true).collect();
+            alphabet.extend(graphemes.iter().cloned());
+
+            for window in graphemes.windows(order) {
+                *ngrams.entry(window.concat()).or_insert(0) += 1;
+                *contexts.entry(window[..order - 1].concat()).or_insert(0) += 1;
+            }
+        }
+
+        NGramModel { order, ngrams, contexts, alphabet: alphabet.len().max(1) }
+    }
+
+    // log probability of `grapheme` following the graphemes in `history`
+    pub fn log_prob(&self, history : &[&str], grapheme : &str) -> f64 {
+        let start = history.len().saturating_sub(self.order - 1);
+        let context = history[start..].concat();
+
+        // no full context yet at the start of a message, those graphemes don't count
+        if history.len() - start < self.order - 1 {
+            return 0.0;
+        }
+
+        let seen = *self.ngrams.get(&format!("{}{}", context, grapheme)).unwrap_or(&0) as f64;
+        let total = *self.contexts.get(&context).unwrap_or(&0) as f64;
+        ((seen + 1.0) / (total + self.alphabet as f64)).ln()
+    }
+}
+
+// Beam search over the columns. Every partial message scores the columns plus
+// `weight` times the language model, and only the best `beam` partial messages
+// are extended with the next column.
+pub fn beam_decode(frequencies : &ColumnFrequencies, pick : Pick, model : &NGramModel, weight : f64, beam : usize) -> Vec<Reading> {
+    let mut readings : Vec<(Vec<String>, f64)> = vec![(Vec::new(), 0.0)];
+
+    for column in column_scores(frequencies, pick) {
+        let mut graphemes : Vec<(&String, &f64)> = column.iter().collect();
+        graphemes.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut extended : Vec<(Vec<String>, f64)> = Vec::new();
+        for &(ref prefix, score) in &readings {
+            let history : Vec<&str> = prefix.iter().map(|g| g.as_str()).collect();
+            for &(grapheme, column_score) in &graphemes {
+                let mut message = prefix.clone();
+                message.push(grapheme.to_string());
+                extended.push((message, score + column_score + weight * model.log_prob(&history, grapheme)));
+            }
+        }
+
+        extended.sort_by(|a, b| b.1.total_cmp(&a.1));
+        extended.truncate(beam.max(1));
+        readings = extended;
+    }
+
+    readings.into_iter()
+        .map(|(graphemes, score)| Reading { message: graphemes.concat(), score })
+        .collect()
+}
+
+#[cfg(test)]
+mod readings {
+    use column_frequencies::ColumnFrequencies;
+    use decoder::{ decode, Pick, TieBreak };
+    use language::{ beam_decode, best_word, NGramModel };
+
+    // columns lean towards "tha", "the" is a close second
+    const NOISY : &str = "tha\ntha\nthe\nthe\nxhe\nyia\nzqa\n";
+
+    #[test]
+    fn it_picks_the_best_fitting_word() {
+        let frequencies = ColumnFrequencies::from_text(NOISY);
+        assert_eq!(decode(&frequencies, Pick::MostCommon, TieBreak::Alphabetical).unwrap().message, "tha");
+
+        let words : Vec<String> = ["she", "the", "tea", "then"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(best_word(&frequencies, Pick::MostCommon, &words).unwrap().message, "the");
+    }
+
+    #[test]
+    fn it_skips_words_that_dont_fit() {
+        let frequencies = ColumnFrequencies::from_text(NOISY);
+        let words : Vec<String> = ["then", "abc"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(best_word(&frequencies, Pick::MostCommon, &words), None);
+    }
+
+    #[test]
+    fn it_scores_ngrams_with_smoothing() {
+        let model = NGramModel::train("the cat\nthe hat\n", 2);
+        assert!(model.log_prob(&["t"], "h") > model.log_prob(&["t"], "a"));
+        assert!(model.log_prob(&["t"], "q").is_finite());
+        assert_eq!(model.log_prob(&[], "t"), 0.0);
+    }
+
+    #[test]
+    fn it_reads_messages_with_an_ngram_model() {
+        let frequencies = ColumnFrequencies::from_text(NOISY);
+        let model = NGramModel::train("the theory of the thermal theme\nthese are the thing\n", 3);
+
+        let readings = beam_decode(&frequencies, Pick::MostCommon, &model, 1.0, 10);
+        assert_eq!(readings[0].message, "the");
+        assert!(readings.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // without the language it's just the columns again
+        assert_eq!(beam_decode(&frequencies, Pick::MostCommon, &model, 0.0, 10)[0].message, "tha");
+    }
+}
@@ -40,48 +40,134 @@ In the above example, the least common character in the first column is a; in th
 Given the recording in your puzzle input and this new decoding methodology, what is the original message that Santa is trying to send?
 */
 
-use std::io::prelude::*;
+extern crate unicode_segmentation;
+
+mod column_frequencies;
+mod decoder;
+mod confidence;
+mod stream;
+mod language;
+
+use column_frequencies::ColumnFrequencies;
+use decoder::{ Pick, TieBreak };
+use language::NGramModel;
+use stream::StreamDecoder;
+use std::io::{ self, prelude::* };
+use std::env;
+use std::fmt::Display;
 use std::fs::File;
-use std::collections::HashMap;
+use std::process;
+use std::str::FromStr;
+
+// partial messages the n-gram reading keeps around
+const BEAM : usize = 100;
+
+// value of `name`, if it was given
+fn option<T>(args : &[String], name : &str) -> Option<T>
+    where T: FromStr, T::Err: Display
+{
+    args.iter().position(|a| a == name).map(|idx| {
+        args.get(idx + 1)
+            .ok_or_else(|| format!("missing value for {}", name))
+            .and_then(|value| value.parse().map_err(|err| format!("invalid value for {}: {}", name, err)))
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            })
+    })
+}
 
-type Column   = HashMap<char, usize>;
+fn read_file(path : &str) -> String {
+    let mut contents = String::new();
+    if let Err(err) = File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        eprintln!("could not read {}: {}", path, err);
+        process::exit(1);
+    }
+    contents
+}
 
 fn main() {
-    let mut f = File::open("input.txt").unwrap();
-    let mut input = String::new();
-    f.read_to_string(&mut input).ok();
+    let args : Vec<String> = env::args().skip(1).collect();
+
+    // `--tie-break alphabetical|first-seen|error` decides between equally common characters
+    let policy = option(&args, "--tie-break").unwrap_or(TieBreak::Alphabetical);
+
+    // `--confidence` shows how sure every column is, `--top <k>` lists the k best messages
+    let show_confidence = args.iter().any(|a| a == "--confidence");
+    let top : Option<usize> = option(&args, "--top");
+
+    // `--words <path>` picks the best fitting word from a word list, one per line.
+    // `--ngrams <path>` reads with a character model of the text in that file,
+    // `--order <n>` and `--weight <w>` tune the model and how much it counts.
+    let words : Option<Vec<String>> = option::<String>(&args, "--words")
+        .map(|path| read_file(&path).lines().map(|w| w.trim().to_string()).filter(|w| !w.is_empty()).collect());
+    let model = option::<String>(&args, "--ngrams")
+        .map(|path| NGramModel::train(&read_file(&path), option(&args, "--order").unwrap_or(3)));
+    let weight = option(&args, "--weight").unwrap_or(1.0);
+
+    // `--stream` decodes stdin while it comes in, `--stable <n>` stops after n lines without changes
+    let frequencies = if args.iter().any(|a| a == "--stream") {
+        let mut decoder = StreamDecoder::new(policy);
+        let stdin = io::stdin();
+        if let Err(err) = decoder.run(stdin.lock(), option(&args, "--stable"), |update| println!("{}", update)) {
+            eprintln!("could not read stdin: {}", err);
+            process::exit(1);
+        }
+        println!("unchanged for the last {} lines", decoder.unchanged());
+        decoder.frequencies().clone()
+    } else {
+        let mut f = File::open("input.txt").unwrap();
+        let mut input = String::new();
+        f.read_to_string(&mut input).ok();
+
+        ColumnFrequencies::from_text(&input)
+    };
+    println!("{} lines, {} columns", frequencies.lines(), frequencies.columns().len());
+
+    let mut undecided = false;
+    for &(part, pick) in &[("A", Pick::MostCommon), ("B", Pick::LeastCommon)] {
+        match decoder::decode(&frequencies, pick, policy) {
+            Ok(decoded) => {
+                for ambiguity in &decoded.ambiguous {
+                    eprintln!("tie broken in {}", ambiguity);
+                }
+                println!("[PART {}] message: {}", part, decoded.message);
+            }
+            Err(ambiguous) => {
+                for ambiguity in &ambiguous {
+                    eprintln!("ambiguous {}", ambiguity);
+                }
+                println!("[PART {}] no unambiguous message", part);
+                undecided = true;
+            }
+        }
 
-    let line_length = input.chars().count() / input.lines().count();
-    let mut columns : Vec<Column> = Vec::with_capacity(line_length);
+        if show_confidence {
+            for column in confidence::column_confidence(&frequencies, pick, policy) {
+                println!("  {}", column);
+            }
+        }
 
-    for _ in 0..line_length {
-        columns.push(Column::new())
-    }
+        if let Some(ref words) = words {
+            match language::best_word(&frequencies, pick, words) {
+                Some(reading) => println!("  best word: {} (score {:.2})", reading.message, reading.score),
+                None => println!("  no word fits the columns"),
+            }
+        }
 
-    for line in input.lines() {
-        for (idx, c) in line.chars().enumerate() {
-            let val = match columns[idx].get(&c) {
-                Some(val) => val + 1,
-                None => 1,
-            };
+        if let Some(ref model) = model {
+            let readings = language::beam_decode(&frequencies, pick, model, weight, BEAM);
+            println!("  best reading: {} (score {:.2})", readings[0].message, readings[0].score);
+        }
 
-            columns[idx].insert(c, val);
+        if let Some(k) = top {
+            for (rank, candidate) in confidence::top_messages(&frequencies, pick, policy, k).iter().enumerate() {
+                println!("  #{} {} (log likelihood {:.2})", rank + 1, candidate.message, candidate.log_likelihood);
+            }
         }
     }
 
-    let message_a : String = columns.iter()
-        .map(|c| *sort_column(&c).last().unwrap())
-        .collect();
-    println!("[PART A] message: {}", message_a);
-
-    let message_b : String = columns.iter()
-        .map(|c| *sort_column(&c).first().unwrap())
-        .collect();
-    println!("[PART B] message: {}", message_b);
+    if undecided {
+        process::exit(1);
+    }
 }
-
-fn sort_column(column : &Column) -> Vec<char> {
-    let mut thingy : Vec<(&char, &usize)> = column.iter().collect();
-    thingy.sort_by_key(|r| r.1);
-    thingy.iter().map(|r| *r.0).collect()
-}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use column_frequencies::ColumnFrequencies;
+use decoder::{ decode, Pick, TieBreak };
+use std::fmt;
+use std::io::{ self, BufRead };
+
+// both messages after some line changed at least one of them. A message is
+// `None` while the `error` tie-break policy can't decide it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    pub line: usize,
+    pub most_common: Option<String>,
+    pub least_common: Option<String>,
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |message : &Option<String>| message.clone().unwrap_or_else(|| "?".to_string());
+        write!(f, "line {}: {} / {}", self.line, show(&self.most_common), show(&self.least_common))
+    }
+}
+
+// Decodes while lines come in, counts are updated one line at a time
+pub struct StreamDecoder {
+    frequencies: ColumnFrequencies,
+    policy: TieBreak,
+    most_common: Option<String>,
+    least_common: Option<String>,
+    // lines since either message last changed
+    unchanged: usize,
+}
+
+impl StreamDecoder {
+    pub fn new(policy : TieBreak) -> StreamDecoder {
+        StreamDecoder {
+            frequencies: ColumnFrequencies::new(),
+            policy,
+            most_common: None,
+            least_common: None,
+            unchanged: 0,
+        }
+    }
+
+    // counts the line, and returns both messages when one of them changed
+    pub fn push(&mut self, line : &str) -> Option<Update> {
+        self.frequencies.add_line(line);
+
+        let most_common = decode(&self.frequencies, Pick::MostCommon, self.policy).ok().map(|d| d.message);
+        let least_common = decode(&self.frequencies, Pick::LeastCommon, self.policy).ok().map(|d| d.message);
+
+        if most_common == self.most_common && least_common == self.least_common {
+            self.unchanged += 1;
+            return None;
+        }
+
+        self.most_common = most_common;
+        self.least_common = least_common;
+        self.unchanged = 0;
+
+        Some(Update {
+            line: self.frequencies.lines(),
+            most_common: self.most_common.clone(),
+            least_common: self.least_common.clone(),
+        })
+    }
+
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    pub fn frequencies(&self) -> &ColumnFrequencies {
+        &self.frequencies
+    }
+
+    // Feeds every line of `reader`, calling `on_update` whenever the messages
+    // change. With `stable_after` it stops once both messages are known and
+    // haven't changed for that many lines.
+    pub fn run<R, F>(&mut self, mut reader : R, stable_after : Option<usize>, mut on_update : F) -> io::Result<()>
+        where R: BufRead,
+              F: FnMut(&Update)
+    {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            if let Some(update) = self.push(&line) {
+                on_update(&update);
+            }
+
+            let decided = self.most_common.is_some() && self.least_common.is_some();
+            if decided && stable_after.is_some_and(|n| self.unchanged >= n) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod streaming {
+    use decoder::TieBreak;
+    use stream::StreamDecoder;
+
+    const EXAMPLE : &str = "eedadn\ndrvtee\neandsr\nraavrd\natevrs\ntsrnev\nsdttsa\nrasrtv\nnssdts\nntnada\nsvetve\ntesnvt\nvntsnd\nvrdear\ndvrsen\nenarar\n";
+
+    #[test]
+    fn it_ends_up_with_the_example_messages() {
+        let mut decoder = StreamDecoder::new(TieBreak::Alphabetical);
+        let mut updates = Vec::new();
+        decoder.run(EXAMPLE.as_bytes(), None, |u| updates.push(u.clone())).unwrap();
+
+        let last = updates.last().unwrap();
+        assert_eq!(last.most_common, Some("easter".to_string()));
+        assert_eq!(last.least_common, Some("advent".to_string()));
+        assert_eq!(decoder.frequencies().lines(), 16);
+        assert_eq!(updates[0].line, 1);
+    }
+
+    #[test]
+    fn it_only_reports_changes() {
+        let mut decoder = StreamDecoder::new(TieBreak::Alphabetical);
+        assert!(decoder.push("ab\r\n").is_some());
+        assert!(decoder.push("ab\r\n").is_none());
+        assert!(decoder.push("ab\r\n").is_none());
+        assert_eq!(decoder.unchanged(), 2);
+
+        let update = decoder.push("cd").unwrap();
+        assert_eq!(update.to_string(), "line 4: ab / cd");
+    }
+
+    #[test]
+    fn it_stops_once_stable() {
+        let input = "ab\nab\nab\nab\nab\ncd\n";
+        let mut decoder = StreamDecoder::new(TieBreak::Alphabetical);
+        decoder.run(input.as_bytes(), Some(3), |_| {}).unwrap();
+        assert_eq!(decoder.frequencies().lines(), 4);
+    }
+
+    #[test]
+    fn it_waits_for_ties_to_be_decided() {
+        let mut decoder = StreamDecoder::new(TieBreak::Error);
+        decoder.push("a");
+        let update = decoder.push("b").unwrap();
+        assert_eq!(update.most_common, None);
+        assert_eq!(update.to_string(), "line 2: ? / ?");
+
+        // still undecided, so it never counts as stable
+        let mut decoder = StreamDecoder::new(TieBreak::Error);
+        decoder.run("a\nb\na\nb\na\nb\n".as_bytes(), Some(1), |_| {}).unwrap();
+        assert_eq!(decoder.frequencies().lines(), 6);
+    }
+}
@@ -0,0 +1,69 @@
+use ip_address::{ IPAddress, IPAddressValidator };
+use palindrome::{ ABA, ABBA };
+use part_a_validator::PartAValidator;
+use part_b_validator::PartBValidator;
+use scan::{ count, count_parallel, Counts };
+use std::time::{ Duration, Instant };
+
+// Addresses shaped like the puzzle input: four segments and three hypernets
+// of 8 to 16 random letters.
+pub fn generate(lines: usize) -> String {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move |below: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % below
+    };
+
+    let mut input = String::with_capacity(lines * 100);
+    for _ in 0..lines {
+        for run in 0..7 {
+            input.push_str(match run {
+                0 => "",
+                _ if run % 2 == 1 => "[",
+                _ => "]",
+            });
+            for _ in 0..8 + next(9) {
+                input.push((b'a' + next(26) as u8) as char);
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
+
+// the original way: parse every line into an IPAddress and run the validators
+fn parsing(input: &str) -> Counts {
+    let mut counts = Counts::default();
+    for line in input.lines() {
+        let address: IPAddress = line.parse().unwrap();
+        counts.addresses += 1;
+        counts.tls += PartAValidator.validate(&address) as usize;
+        counts.ssl += PartBValidator.validate(&address) as usize;
+    }
+    counts
+}
+
+fn measure<F: Fn() -> Counts>(name: &str, f: F) -> Duration {
+    let start = Instant::now();
+    let counts = f();
+    let elapsed = start.elapsed();
+
+    let rate = counts.addresses as f64 / elapsed.as_secs_f64();
+    println!("{:<16} {:>8.0?} {:>12.0} addresses/s (TLS: {}, SSL: {})", name, elapsed, rate, counts.tls, counts.ssl);
+    elapsed
+}
+
+// validates `lines` generated addresses every way and prints the speedups
+pub fn run(lines: usize, threads: usize) {
+    let input = generate(lines);
+
+    let slow = measure("parsing", || parsing(&input));
+    let fast = measure("byte scan", || count(input.as_bytes(), &ABBA, &ABA).unwrap());
+    println!("speedup: {:.2}x", slow.as_secs_f64() / fast.as_secs_f64());
+
+    let name = format!("byte scan x{}", threads);
+    let parallel = measure(&name, || count_parallel(input.as_bytes(), &ABBA, &ABA, threads).unwrap());
+    println!("speedup over byte scan: {:.2}x", fast.as_secs_f64() / parallel.as_secs_f64());
+}
@@ -0,0 +1,324 @@
+use evidence::Explanation;
+use ip_address::{ IPAddress, IPAddressValidator };
+use part_a_validator::PartAValidator;
+use part_b_validator::PartBValidator;
+
+// a validator picked at runtime
+pub type Policy = Box<dyn IPAddressValidator + Send + Sync>;
+
+// both validators pass
+pub struct And<A, B>(pub A, pub B);
+impl<A: IPAddressValidator, B: IPAddressValidator> IPAddressValidator for And<A, B> {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        self.0.validate(subject) && self.1.validate(subject)
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        combine(&[self.0.validate_explained(subject), self.1.validate_explained(subject)], |v| v == 2)
+    }
+}
+
+// either validator passes
+pub struct Or<A, B>(pub A, pub B);
+impl<A: IPAddressValidator, B: IPAddressValidator> IPAddressValidator for Or<A, B> {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        self.0.validate(subject) || self.1.validate(subject)
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        combine(&[self.0.validate_explained(subject), self.1.validate_explained(subject)], |v| v >= 1)
+    }
+}
+
+// the validator fails
+pub struct Not<A>(pub A);
+impl<A: IPAddressValidator> IPAddressValidator for Not<A> {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        !self.0.validate(subject)
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        let explanation = self.0.validate_explained(subject);
+        Explanation::new(!explanation.valid, explanation.evidence)
+    }
+}
+
+// at least `at_least` of the validators pass
+pub struct Count {
+    pub at_least: usize,
+    pub validators: Vec<Policy>,
+}
+
+impl IPAddressValidator for Count {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        self.validators.iter().filter(|v| v.validate(subject)).count() >= self.at_least
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        let explanations: Vec<Explanation> = self.validators.iter().map(|v| v.validate_explained(subject)).collect();
+        combine(&explanations, |v| v >= self.at_least)
+    }
+}
+
+// every validator's evidence, with a verdict on how many of them passed
+fn combine<F: Fn(usize) -> bool>(explanations: &[Explanation], verdict: F) -> Explanation {
+    let valid = explanations.iter().filter(|e| e.valid).count();
+    Explanation::new(verdict(valid), explanations.iter().flat_map(|e| e.evidence.iter().cloned()).collect())
+}
+
+// Composes a policy from left to right, e.g. "supports TLS but not SSL":
+// PolicyBuilder::new(PartAValidator).and_not(PartBValidator).build()
+pub struct PolicyBuilder {
+    policy: Policy,
+}
+
+impl PolicyBuilder {
+    pub fn new<V: IPAddressValidator + Send + Sync + 'static>(validator: V) -> PolicyBuilder {
+        PolicyBuilder { policy: Box::new(validator) }
+    }
+
+    pub fn at_least(at_least: usize, validators: Vec<Policy>) -> PolicyBuilder {
+        PolicyBuilder::new(Count { at_least, validators })
+    }
+
+    pub fn and<V: IPAddressValidator + Send + Sync + 'static>(self, validator: V) -> PolicyBuilder {
+        PolicyBuilder::new(And(self.policy, validator))
+    }
+
+    pub fn or<V: IPAddressValidator + Send + Sync + 'static>(self, validator: V) -> PolicyBuilder {
+        PolicyBuilder::new(Or(self.policy, validator))
+    }
+
+    pub fn and_not<V: IPAddressValidator + Send + Sync + 'static>(self, validator: V) -> PolicyBuilder {
+        PolicyBuilder::new(And(self.policy, Not(validator)))
+    }
+
+    pub fn negate(self) -> PolicyBuilder {
+        PolicyBuilder::new(Not(self.policy))
+    }
+
+    pub fn build(self) -> Policy {
+        self.policy
+    }
+}
+
+// Reads a policy like "tls & !ssl", "tls | ssl" or "2of(tls, ssl, !tls)".
+// `!` binds tightest, then `&`, then `|`, and parentheses group.
+pub fn parse_policy(s: &str) -> Result<Policy, String> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser { tokens: &tokens, position: 0 };
+    let policy = parser.or()?;
+
+    match parser.peek() {
+        None => Ok(policy.build()),
+        Some(token) => Err(format!("unexpected '{}' in policy '{}'", token, s)),
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if "!&|(),".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if c.is_ascii_alphanumeric() {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_alphanumeric() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        } else {
+            return Err(format!("unexpected '{}' in policy '{}'", c, s));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected '{}', found '{}'", expected, token)),
+            None => Err(format!("expected '{}', found the end of the policy", expected)),
+        }
+    }
+
+    fn or(&mut self) -> Result<PolicyBuilder, String> {
+        let mut policy = self.and()?;
+        while self.peek() == Some("|") {
+            self.next();
+            policy = policy.or(self.and()?.build());
+        }
+        Ok(policy)
+    }
+
+    fn and(&mut self) -> Result<PolicyBuilder, String> {
+        let mut policy = self.not()?;
+        while self.peek() == Some("&") {
+            self.next();
+            policy = if self.peek() == Some("!") {
+                self.next();
+                policy.and_not(self.not()?.build())
+            } else {
+                policy.and(self.not()?.build())
+            };
+        }
+        Ok(policy)
+    }
+
+    fn not(&mut self) -> Result<PolicyBuilder, String> {
+        if self.peek() == Some("!") {
+            self.next();
+            return Ok(self.not()?.negate());
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<PolicyBuilder, String> {
+        match self.next() {
+            Some("tls") => Ok(PolicyBuilder::new(PartAValidator)),
+            Some("ssl") => Ok(PolicyBuilder::new(PartBValidator)),
+            Some("(") => {
+                let policy = self.or()?;
+                self.expect(")")?;
+                Ok(policy)
+            }
+            Some(token) if token.ends_with("of") && token[..token.len() - 2].parse::<usize>().is_ok() => {
+                let at_least = token[..token.len() - 2].parse().unwrap();
+                self.expect("(")?;
+
+                let mut validators = vec![self.or()?.build()];
+                while self.peek() == Some(",") {
+                    self.next();
+                    validators.push(self.or()?.build());
+                }
+                self.expect(")")?;
+
+                Ok(PolicyBuilder::at_least(at_least, validators))
+            }
+            Some(token) => Err(format!("unknown validator '{}', expected tls, ssl or <n>of(...)", token)),
+            None => Err("policy ended too soon".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod combine {
+    use combinators::{ And, Count, Not, Or, Policy, PolicyBuilder };
+    use ip_address::{ IPAddress, IPAddressValidator };
+    use part_a_validator::PartAValidator;
+    use part_b_validator::PartBValidator;
+
+    // supports TLS, SSL, both and neither
+    fn addresses() -> Vec<IPAddress> {
+        ["abba[mnop]qrst", "aba[bab]xyz", "abba[bab]aba", "abcd[efgh]ijkl"].iter()
+            .map(|a| a.parse().unwrap())
+            .collect()
+    }
+
+    fn matches<V: IPAddressValidator + ?Sized>(validator: &V) -> Vec<bool> {
+        addresses().iter().map(|a| a.is_valid(validator)).collect()
+    }
+
+    #[test]
+    fn it_combines_validators() {
+        assert_eq!(matches(&And(PartAValidator, PartBValidator)), vec![false, false, true, false]);
+        assert_eq!(matches(&Or(PartAValidator, PartBValidator)), vec![true, true, true, false]);
+        assert_eq!(matches(&Not(PartAValidator)), vec![false, true, false, true]);
+        assert_eq!(matches(&And(PartAValidator, Not(PartBValidator))), vec![true, false, false, false]);
+    }
+
+    #[test]
+    fn it_counts_passing_validators() {
+        let validators = || -> Vec<Policy> { vec![Box::new(PartAValidator), Box::new(PartBValidator)] };
+        assert_eq!(matches(&Count { at_least: 1, validators: validators() }), vec![true, true, true, false]);
+        assert_eq!(matches(&Count { at_least: 2, validators: validators() }), vec![false, false, true, false]);
+        assert_eq!(matches(&Count { at_least: 0, validators: validators() }), vec![true, true, true, true]);
+    }
+
+    #[test]
+    fn it_builds_policies() {
+        let tls_only = PolicyBuilder::new(PartAValidator).and_not(PartBValidator).build();
+        assert_eq!(matches(&tls_only), vec![true, false, false, false]);
+
+        let neither = PolicyBuilder::new(PartAValidator).or(PartBValidator).negate().build();
+        assert_eq!(matches(&*neither), vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn it_explains_combined_verdicts() {
+        let address: IPAddress = "abba[bab]aba".parse().unwrap();
+        let tls_only = PolicyBuilder::new(PartAValidator).and_not(PartBValidator).build();
+
+        let explanation = tls_only.validate_explained(&address);
+        assert!(!explanation.valid);
+        assert_eq!(explanation.evidence.len(), 2);
+        assert_eq!(explanation.to_string(), "no: ABBA abba in segment 0 at offset 0 (byte 0); \
+            ABA aba in segment 1 at offset 0 (byte 9), BAB bab in hypernet 0 at offset 0 (byte 5)");
+
+        for address in &addresses() {
+            assert_eq!(tls_only.validate_explained(address).valid, tls_only.validate(address));
+        }
+    }
+
+    #[test]
+    fn it_validates_with_trait_objects() {
+        let validators : Vec<Box<dyn IPAddressValidator>> = vec![Box::new(PartAValidator), Box::new(PartBValidator)];
+        let address : IPAddress = "abba[bab]aba".parse().unwrap();
+        assert!(validators.iter().all(|v| address.is_valid(v)));
+        assert!(validators.iter().all(|v| address.is_valid(v.as_ref())));
+    }
+}
+
+#[cfg(test)]
+mod parse_policy {
+    use combinators::parse_policy;
+    use ip_address::IPAddress;
+
+    fn matches(policy: &str) -> Vec<bool> {
+        let policy = parse_policy(policy).unwrap();
+        ["abba[mnop]qrst", "aba[bab]xyz", "abba[bab]aba", "abcd[efgh]ijkl"].iter()
+            .map(|a| a.parse::<IPAddress>().unwrap().is_valid(&policy))
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_policies() {
+        assert_eq!(matches("tls"), vec![true, false, true, false]);
+        assert_eq!(matches("tls & !ssl"), vec![true, false, false, false]);
+        assert_eq!(matches("!tls | ssl"), vec![false, true, true, true]);
+        assert_eq!(matches("!(tls | ssl)"), vec![false, false, false, true]);
+        assert_eq!(matches("2of(tls, ssl, !tls)"), vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn it_rejects_invalid_policies() {
+        for policy in &["", "tls &", "tls ssl", "(tls", "tsl", "2of tls", "tls $ ssl"] {
+            assert!(parse_policy(policy).is_err(), "{:?} should be invalid", policy);
+        }
+    }
+}
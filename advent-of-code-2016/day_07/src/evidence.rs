@@ -0,0 +1,82 @@
+use std::fmt;
+
+// the two kinds of runs an address is made of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Section {
+    Segment,
+    Hypernet,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Section::Segment => write!(f, "segment"),
+            Section::Hypernet => write!(f, "hypernet"),
+        }
+    }
+}
+
+// a sequence found in one of the segments or hypernets of an address
+#[derive(Debug, Clone, PartialEq)]
+pub struct Found {
+    pub text: String,
+    pub section: Section,
+    // which segment or hypernet, counting from 0
+    pub index: usize,
+    // byte offset within that segment or hypernet, and within the full address
+    pub offset: usize,
+    pub position: usize,
+}
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in {} {} at offset {} (byte {})", self.text, self.section, self.index, self.offset, self.position)
+    }
+}
+
+// what made a validator decide the way it did
+#[derive(Debug, Clone, PartialEq)]
+pub enum Evidence {
+    // an ABBA, which supports TLS in a segment and rules it out in a hypernet
+    Abba(Found),
+    // an ABA in a segment with its BAB in a hypernet
+    AbaBab { aba: Found, bab: Found },
+    // an ABA in a segment without a BAB in any hypernet
+    UnmatchedAba(Found),
+    // nothing was found at all
+    Missing(&'static str),
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Evidence::Abba(ref abba) => write!(f, "ABBA {}", abba),
+            Evidence::AbaBab { ref aba, ref bab } => write!(f, "ABA {}, BAB {}", aba, bab),
+            Evidence::UnmatchedAba(ref aba) => write!(f, "ABA {} without a BAB", aba),
+            Evidence::Missing(what) => write!(f, "{}", what),
+        }
+    }
+}
+
+// a validation result with the evidence it was based on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub valid: bool,
+    pub evidence: Vec<Evidence>,
+}
+
+impl Explanation {
+    pub fn new(valid: bool, evidence: Vec<Evidence>) -> Explanation {
+        Explanation { valid, evidence }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", if self.valid { "yes" } else { "no" })?;
+        for (idx, evidence) in self.evidence.iter().enumerate() {
+            write!(f, "{} {}", if idx == 0 { ":" } else { ";" }, evidence)?;
+        }
+        Ok(())
+    }
+}
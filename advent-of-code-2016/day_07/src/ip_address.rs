@@ -1,62 +1,183 @@
+use evidence::{ Explanation, Found, Section };
+use std::fmt;
 use std::str::FromStr;
-use regex::Regex;
-
-lazy_static!{
-    static ref REGEX : Regex = Regex::new(r"\[(?P<hypernet>[a-z]*)\]|(?P<segment>[a-z]*)+").unwrap(); 
-}
 
 pub type Segments = Vec<String>;
 pub type Hypernets = Vec<String>;
 
+// byte range of a segment or hypernet within the full address
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
 #[derive(Debug)]
 pub struct IPAddress {
     pub full_address: String,
     pub segments: Segments,
     pub hypernets: Hypernets,
+    // where every segment and hypernet was found, in the same order
+    pub segment_spans: Vec<Span>,
+    pub hypernet_spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ParseOptions {
+    // `a[b[c]d]e` is an error, unless this is set. Every bracketed run at
+    // any depth then counts as a hypernet of its own: b, c and d.
+    pub allow_nested: bool,
+}
+
+// what is wrong with an address, and at which byte
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at byte {}: {}", self.position, self.message)
+    }
 }
 
 pub trait IPAddressValidator {
     fn validate(&self, subject: &IPAddress) -> bool;
+
+    // the same verdict as `validate`, with what it was based on
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        Explanation::new(self.validate(subject), Vec::new())
+    }
+}
+
+// so boxed and borrowed validators, trait objects included, can be combined too
+impl<T: IPAddressValidator + ?Sized> IPAddressValidator for Box<T> {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        (**self).validate(subject)
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        (**self).validate_explained(subject)
+    }
+}
+
+impl<T: IPAddressValidator + ?Sized> IPAddressValidator for &T {
+    fn validate(&self, subject: &IPAddress) -> bool {
+        (**self).validate(subject)
+    }
+
+    fn validate_explained(&self, subject: &IPAddress) -> Explanation {
+        (**self).validate_explained(subject)
+    }
 }
 
 impl IPAddress {
-    pub fn is_valid<T: IPAddressValidator>(&self, validator: &T) -> bool {
-        validator.validate(&self)
+    pub fn is_valid<T: IPAddressValidator + ?Sized>(&self, validator: &T) -> bool {
+        validator.validate(self)
+    }
+
+    pub fn sections(&self, section: Section) -> &[String] {
+        match section {
+            Section::Segment => &self.segments,
+            Section::Hypernet => &self.hypernets,
+        }
+    }
+
+    // describes the `len` bytes at `offset` in one of the segments or hypernets
+    pub fn found(&self, section: Section, index: usize, offset: usize, len: usize) -> Found {
+        let spans = match section {
+            Section::Segment => &self.segment_spans,
+            Section::Hypernet => &self.hypernet_spans,
+        };
+
+        Found {
+            text: self.sections(section)[index][offset..offset + len].to_string(),
+            section,
+            index,
+            offset,
+            position: spans[index].start + offset,
+        }
     }
 
-    fn dissect_address(s: &str) -> (Segments, Hypernets) {
-        let initial: (Segments, Hypernets) = (Vec::new(), Vec::new());
-        REGEX.captures_iter(&s).fold(initial, |matches, capture| {
-            let mut matches = matches.clone();
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<IPAddress, ParseError> {
+        let error = |position: usize, message: String| Err(ParseError { position, message });
+
+        let mut address = IPAddress {
+            full_address: s.to_string(),
+            segments: Vec::new(),
+            hypernets: Vec::new(),
+            segment_spans: Vec::new(),
+            hypernet_spans: Vec::new(),
+        };
+
+        if s.is_empty() {
+            return error(0, "empty address".to_string());
+        }
 
-            if let Some(segment) = capture.name("segment") {
-                matches.0.push(segment.as_str().to_string());
+        // positions of the brackets that are still open
+        let mut open: Vec<usize> = Vec::new();
+        let mut start = 0;
+
+        for (position, c) in s.char_indices() {
+            match c {
+                'a'..='z' => continue,
+                '[' if !open.is_empty() && !options.allow_nested => {
+                    return error(position, format!("nested '[' inside the hypernet opened at byte {}", open[0]));
+                }
+                '[' => open.push(position),
+                ']' if open.is_empty() => return error(position, "']' without a matching '['".to_string()),
+                ']' if open.last() == Some(&(position - 1)) => return error(position - 1, "empty hypernet".to_string()),
+                ']' => {}
+                _ => return error(position, format!("unexpected {:?}, only a-z and brackets are allowed", c)),
             }
 
-            if let Some(hypernet) = capture.name("hypernet") {
-                matches.1.push(hypernet.as_str().to_string());
+            // a bracket ends whatever came before it. Brackets that were
+            // open before this one mean that was (part of) a hypernet.
+            let inside = if c == '[' { open.len() > 1 } else { true };
+            address.push(inside, s, Span { start, end: position });
+            start = position + 1;
+
+            if c == ']' {
+                open.pop();
             }
+        }
+
+        if let Some(&position) = open.last() {
+            return error(position, "'[' is never closed".to_string());
+        }
+
+        address.push(false, s, Span { start, end: s.len() });
+        Ok(address)
+    }
+
+    // empty runs, like the one between `][`, aren't segments or hypernets
+    fn push(&mut self, hypernet: bool, s: &str, span: Span) {
+        if span.start == span.end {
+            return;
+        }
 
-            matches
-        })
+        let text = s[span.start..span.end].to_string();
+        if hypernet {
+            self.hypernets.push(text);
+            self.hypernet_spans.push(span);
+        } else {
+            self.segments.push(text);
+            self.segment_spans.push(span);
+        }
     }
 }
 
 impl FromStr for IPAddress {
-    type Err = String;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (segments, hypernets) = IPAddress::dissect_address(&s);
-        Ok(IPAddress {
-            full_address: String::from_str(&s).unwrap(),
-            segments: segments,
-            hypernets: hypernets,
-        })
+        IPAddress::parse_with(s, &ParseOptions::default())
     }
 }
 
 #[cfg(test)]
 mod parse_from_str {
-    use ip_address::IPAddress;
+    use ip_address::{ IPAddress, ParseOptions, Span };
 
     #[test]
     fn it_should_parse_from_str() {
@@ -65,4 +186,48 @@ mod parse_from_str {
         assert_eq!(addr.hypernets, vec!["ip", "with"]);
         assert_eq!(addr.segments, vec!["some", "address", "brackets"]);
     }
+
+    #[test]
+    fn it_should_record_spans() {
+        let addr: IPAddress = "[ab]cd[ef]".parse().unwrap();
+        assert_eq!(addr.segments, vec!["cd"]);
+        assert_eq!(addr.segment_spans, vec![Span { start: 4, end: 6 }]);
+        assert_eq!(addr.hypernets, vec!["ab", "ef"]);
+        assert_eq!(addr.hypernet_spans, vec![Span { start: 1, end: 3 }, Span { start: 7, end: 9 }]);
+
+        for (hypernet, span) in addr.hypernets.iter().zip(addr.hypernet_spans.iter()) {
+            assert_eq!(&addr.full_address[span.start..span.end], hypernet);
+        }
+    }
+
+    #[test]
+    fn it_should_reject_malformed_addresses() {
+        let cases = [
+            ("", 0),
+            ("abc]def", 3),
+            ("abc[def", 3),
+            ("ab[cd]e[f", 7),
+            ("ab[]cd", 2),
+            ("ab[c[d]e]f", 4),
+            ("abC", 2),
+            ("ab cd", 2),
+        ];
+
+        for &(address, position) in &cases {
+            let err = address.parse::<IPAddress>().unwrap_err();
+            assert_eq!(err.position, position, "{:?}: {}", address, err);
+        }
+    }
+
+    #[test]
+    fn it_should_allow_nested_hypernets_when_asked() {
+        let options = ParseOptions { allow_nested: true };
+        let addr = IPAddress::parse_with("ab[c[d]e]f", &options).unwrap();
+        assert_eq!(addr.segments, vec!["ab", "f"]);
+        assert_eq!(addr.hypernets, vec!["c", "d", "e"]);
+        assert_eq!(addr.hypernet_spans[1], Span { start: 5, end: 6 });
+
+        assert_eq!(IPAddress::parse_with("a[b[c]", &options).unwrap_err().position, 1);
+        assert_eq!(IPAddress::parse_with("a[b]]", &options).unwrap_err().position, 4);
+    }
 }
@@ -0,0 +1,10 @@
+// Everything but the command line lives here, so tests under tests/ can use it too.
+
+pub mod evidence;
+pub mod ip_address;
+pub mod palindrome;
+pub mod part_a_validator;
+pub mod part_b_validator;
+pub mod combinators;
+pub mod scan;
+pub mod bench;
@@ -28,30 +28,75 @@
 // How many IPs in your puzzle input support SSL?
 //
 
-extern crate regex;
-#[macro_use]
-extern crate lazy_static;
 
-mod ip_address;
-mod part_a_validator;
-mod part_b_validator;
+extern crate day_7;
 
 use std::io::prelude::*;
+use std::env;
 use std::fs::File;
-use ip_address::IPAddress;
+use day_7::{ bench, combinators, scan };
+use day_7::ip_address::{ IPAddress, IPAddressValidator, ParseOptions };
 
-use part_a_validator::PartAValidator;
-use part_b_validator::PartBValidator;
+use day_7::palindrome::{ Palindrome, ABA, ABBA };
+use day_7::part_a_validator::{ PartAValidator, TlsValidator };
+use day_7::part_b_validator::{ PartBValidator, SslValidator };
+use std::process;
+use std::thread;
 
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `--threads <n>` for `scan` and `bench`, all cores by default
+    let threads = match args.iter().position(|a| a == "--threads") {
+        Some(idx) => args.get(idx + 1)
+            .ok_or_else(|| "missing value for --threads".to_string())
+            .and_then(|n| match n.parse() {
+                Ok(0) => Err("invalid value for --threads: needs at least one thread".to_string()),
+                Ok(n) => Ok(n),
+                Err(err) => Err(format!("invalid value for --threads: {}", err)),
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            }),
+        None => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    };
+
+    let command = args.first().map(|a| a.as_str());
+
+    // the byte scan only knows flat hypernets
+    if (command == Some("bench") || command == Some("scan")) && args.iter().any(|a| a == "--allow-nested") {
+        eprintln!("--allow-nested can't be used with {}", command.unwrap());
+        process::exit(1);
+    }
+
+    // `bench [lines]` compares parsing against the byte scan, on a million addresses by default
+    if command == Some("bench") {
+        let lines = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(1_000_000);
+        return bench::run(lines, threads);
+    }
+
+    // `scan <path>` counts TLS and SSL support in a file of any size, without parsing
+    if command == Some("scan") {
+        return scan_file(args.get(1).map_or("input.txt", |p| p.as_str()), threads);
+    }
+
     // read input from file
     let mut f = File::open("input.txt").unwrap();
     let mut input = String::new();
     f.read_to_string(&mut input).ok();
 
+    // `--allow-nested` accepts hypernets inside hypernets, like `a[b[c]d]e`
+    let options = ParseOptions { allow_nested: args.iter().any(|a| a == "--allow-nested") };
+
     // parse lines to IPAddress objects
-    let addresses: Vec<IPAddress> = input.lines().map(|l| l.parse().unwrap()).collect();
+    let addresses: Vec<IPAddress> = input.lines().enumerate()
+        .map(|(idx, l)| IPAddress::parse_with(l, &options).unwrap_or_else(|err| {
+            eprintln!("invalid address on line {}, {}", idx + 1, err);
+            process::exit(1);
+        }))
+        .collect();
 
     // figure out answer A
     let part_a_validator = PartAValidator{};
@@ -62,4 +107,79 @@ fn main() {
     let part_b_validator = PartBValidator{};
     let answer_b: Vec<&IPAddress> = addresses.iter().filter(|a| a.is_valid(&part_b_validator)).collect();
     println!("[PART B] answer: {}", answer_b.len());
+
+    // `--marker <k>` counts TLS and SSL support for the protocol variants with k character markers
+    if let Some(idx) = args.iter().position(|a| a == "--marker") {
+        let marker = args.get(idx + 1)
+            .ok_or_else(|| "missing value for --marker".to_string())
+            .and_then(|k| k.parse().map_err(|err| format!("invalid value for --marker: {}", err)))
+            .and_then(Palindrome::new)
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            });
+
+        let tls = addresses.iter().filter(|a| a.is_valid(&TlsValidator(marker))).count();
+        let ssl = addresses.iter().filter(|a| a.is_valid(&SslValidator(marker))).count();
+        println!("[MARKER {}] TLS: {}, SSL: {}", marker.len, tls, ssl);
+    }
+
+    // `--policy "tls & !ssl"` counts addresses for a combined policy, `--list` prints them too
+    let policy = args.iter().position(|a| a == "--policy").map(|idx| {
+        args.get(idx + 1)
+            .ok_or_else(|| "missing value for --policy".to_string())
+            .and_then(|p| combinators::parse_policy(p))
+            .unwrap_or_else(|err| {
+                eprintln!("invalid policy: {}", err);
+                process::exit(1);
+            })
+    });
+
+    if let Some(ref policy) = policy {
+        let matching: Vec<&IPAddress> = addresses.iter().filter(|a| a.is_valid(policy)).collect();
+        if args.iter().any(|a| a == "--list") {
+            for address in &matching {
+                println!("{}", address.full_address);
+            }
+        }
+        println!("[POLICY] answer: {}", matching.len());
+    }
+
+    // `--explain` prints every address with what the validators based their verdict on
+    if args.iter().any(|a| a == "--explain") {
+        for address in &addresses {
+            println!("{}", address.full_address);
+            println!("  TLS: {}", part_a_validator.validate_explained(address));
+            println!("  SSL: {}", part_b_validator.validate_explained(address));
+            if let Some(ref policy) = policy {
+                println!("  POLICY: {}", policy.validate_explained(address));
+            }
+        }
+    }
+}
+
+fn scan_file(path: &str, threads: usize) {
+    let mut input = Vec::new();
+    if let Err(err) = File::open(path).and_then(|mut f| f.read_to_end(&mut input)) {
+        eprintln!("could not read {}: {}", path, err);
+        process::exit(1);
+    }
+
+    match scan::count_parallel(&input, &ABBA, &ABA, threads) {
+        Ok(counts) => {
+            println!("{} addresses", counts.addresses);
+            println!("[PART A] answer: {}", counts.tls);
+            println!("[PART B] answer: {}", counts.ssl);
+        }
+        Err(err) => {
+            // the parser says what is wrong with it
+            let line = input.split(|&b| b == b'\n').nth(err.line - 1).unwrap_or(&[]);
+            let line = String::from_utf8_lossy(line);
+            match line.trim_end_matches('\r').parse::<IPAddress>() {
+                Err(parse_error) => eprintln!("invalid address on line {}, {}", err.line, parse_error),
+                Ok(_) => eprintln!("invalid address on line {}, at byte {}", err.line, err.position),
+            }
+            process::exit(1);
+        }
+    }
 }
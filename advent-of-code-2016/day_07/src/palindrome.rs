@@ -0,0 +1,130 @@
+// A window of `len` bytes that reads the same backwards, like abba or aba.
+// With `distinct` set, every character also has to differ from the one
+// just inside it, which rules out aaaa and abbba but still allows abcba.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palindrome {
+    pub len: usize,
+    pub distinct: bool,
+}
+
+// the markers from the puzzle
+pub const ABBA: Palindrome = Palindrome { len: 4, distinct: true };
+pub const ABA: Palindrome = Palindrome { len: 3, distinct: true };
+
+impl Palindrome {
+    // anything shorter has no inner characters to swap for a counterpart
+    pub fn new(len: usize) -> Result<Palindrome, String> {
+        if len < 3 {
+            return Err(format!("markers need at least 3 characters, got {}", len));
+        }
+        Ok(Palindrome { len, distinct: true })
+    }
+
+    #[inline]
+    pub fn matches(&self, window: &[u8]) -> bool {
+        // the puzzle's markers have a fixed width, which is a lot quicker to check
+        match (self.distinct, window) {
+            (true, &[a, b, c, d]) if self.len == 4 => return a == d && b == c && a != b,
+            (true, &[a, b, c]) if self.len == 3 => return a == c && a != b,
+            _ if window.len() != self.len => return false,
+            _ => {}
+        }
+
+        // most windows already differ on the outer characters
+        if window[0] != window[self.len - 1] || (self.distinct && window[0] == window[1]) {
+            return false;
+        }
+
+        let half = self.len / 2;
+        (1..half).all(|idx| window[idx] == window[self.len - 1 - idx])
+            && (!self.distinct || (1..(self.len - 1) / 2).all(|idx| window[idx] != window[idx + 1]))
+    }
+
+    // offsets of every matching window, overlapping ones included
+    pub fn positions<'a>(&self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let marker = *self;
+        haystack.windows(self.len)
+            .enumerate()
+            .filter(move |&(_, window)| marker.matches(window))
+            .map(|(idx, _)| idx)
+    }
+
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        haystack.windows(self.len).position(|window| self.matches(window))
+    }
+
+    // The counterpart swaps the two outermost layers, so aba becomes bab
+    // and abcba becomes bacab.
+    pub fn is_counterpart(&self, window: &[u8], other: &[u8]) -> bool {
+        if window.len() != self.len || other.len() != self.len {
+            return false;
+        }
+
+        (0..self.len).all(|idx| {
+            let layer = idx.min(self.len - 1 - idx);
+            let swapped = match layer {
+                0 => 1,
+                1 => 0,
+                _ => layer,
+            };
+            other[idx] == window[swapped]
+        })
+    }
+
+    pub fn find_counterpart(&self, window: &[u8], haystack: &[u8]) -> Option<usize> {
+        if window.len() != self.len {
+            return None;
+        }
+
+        // a counterpart starts with the second character of the window
+        (0..(haystack.len() + 1).saturating_sub(self.len))
+            .find(|&idx| haystack[idx] == window[1] && self.is_counterpart(window, &haystack[idx..idx + self.len]))
+    }
+}
+
+#[cfg(test)]
+mod windows {
+    use palindrome::{ Palindrome, ABA, ABBA };
+
+    #[test]
+    fn it_matches_markers_of_any_length() {
+        let five = Palindrome::new(5).unwrap();
+        assert!(five.matches(b"abcba"));
+        assert!(five.matches(b"abxba"));
+        assert!(!five.matches(b"abbba"));
+        assert!(!five.matches(b"abcab"));
+
+        let six = Palindrome::new(6).unwrap();
+        assert!(six.matches(b"abccba"));
+        assert!(!six.matches(b"aabbaa"));
+        assert!(!six.matches(b"abcba"));
+
+        assert!(Palindrome::new(2).is_err());
+    }
+
+    #[test]
+    fn it_can_ignore_the_distinct_rule() {
+        let any = Palindrome { len: 4, distinct: false };
+        assert!(any.matches(b"aaaa"));
+        assert!(!ABBA.matches(b"aaaa"));
+    }
+
+    #[test]
+    fn it_finds_overlapping_windows() {
+        assert_eq!(ABA.positions(b"zazbz").collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(ABBA.find(b"xyzoxxo"), Some(3));
+        assert_eq!(ABBA.find(b"abb"), None);
+        assert_eq!(ABBA.find(b""), None);
+    }
+
+    #[test]
+    fn it_finds_counterparts() {
+        assert!(ABA.is_counterpart(b"aba", b"bab"));
+        assert!(!ABA.is_counterpart(b"aba", b"aba"));
+
+        let five = Palindrome::new(5).unwrap();
+        assert!(five.is_counterpart(b"abcba", b"bacab"));
+        assert_eq!(five.find_counterpart(b"abcba", b"xxbacabxx"), Some(2));
+        assert_eq!(five.find_counterpart(b"abcba", b"bacbx"), None);
+    }
+}
@@ -1,71 +1,88 @@
+use evidence::{ Evidence, Explanation, Section };
 use ip_address::{ IPAddress, IPAddressValidator };
+use palindrome::{ Palindrome, ABBA };
 
-pub struct PartAValidator;
-impl IPAddressValidator for PartAValidator {
+// supports TLS: a marker in a segment, but none in a hypernet
+pub struct TlsValidator(pub Palindrome);
+impl IPAddressValidator for TlsValidator {
     fn validate(&self, ip_address: &IPAddress) -> bool {
+        let marker = &self.0;
 
-        if ip_address.hypernets.iter().any(contains_abba) {
+        if ip_address.hypernets.iter().any(|s| marker.find(s.as_bytes()).is_some()) {
             return false;
         }
 
-        if ip_address.segments.iter().any(contains_abba) {
-            return true;
+        ip_address.segments.iter().any(|s| marker.find(s.as_bytes()).is_some())
+    }
+
+    fn validate_explained(&self, ip_address: &IPAddress) -> Explanation {
+        // a marker in a hypernet decides it, wherever the other markers are
+        for &(section, valid) in &[(Section::Hypernet, false), (Section::Segment, true)] {
+            for (index, s) in ip_address.sections(section).iter().enumerate() {
+                if let Some(offset) = self.0.find(s.as_bytes()) {
+                    return Explanation::new(valid, vec![Evidence::Abba(ip_address.found(section, index, offset, self.0.len))]);
+                }
+            }
         }
 
-        false
+        Explanation::new(false, vec![Evidence::Missing("no ABBA in any segment")])
     }
 }
 
-// accepts a whole string and tries to find an ABBA
-fn contains_abba(input: &String) -> bool {
-    let chars: Vec<String> = input.chars().map(|v| v.to_string()).collect();
-    chars.windows(4).any(|s| { is_abba(s.join("").as_str()) })
-}
+// the puzzle's TLS, with ABBA markers
+pub struct PartAValidator;
+impl IPAddressValidator for PartAValidator {
+    fn validate(&self, ip_address: &IPAddress) -> bool {
+        TlsValidator(ABBA).validate(ip_address)
+    }
 
-// checks if passed string is an ABBA
-fn is_abba(input: &str) -> bool {
-    input[0..1] == input[3..4] && input[1..2] == input[2..3] && input[0..1] != input[1..2]
+    fn validate_explained(&self, ip_address: &IPAddress) -> Explanation {
+        TlsValidator(ABBA).validate_explained(ip_address)
+    }
 }
 
 #[cfg(test)]
 mod is_abba {
-    use part_a_validator::is_abba;
+    use palindrome::ABBA;
 
     #[test]
     fn should_detect_valid_abbas() {
-        assert!(is_abba("abba"));
+        assert!(ABBA.matches(b"abba"));
     }
 
     #[test]
     fn should_ignore_invalid_sequences() {
-        assert!(!is_abba("azop"));
+        assert!(!ABBA.matches(b"azop"));
     }
 
     #[test]
     fn should_ignore_if_four_characters_match() {
-        assert!(!is_abba("aaaa"));
+        assert!(!ABBA.matches(b"aaaa"));
     }
 }
 
 #[cfg(test)]
 mod contains_abba {
-    use part_a_validator::contains_abba;
+    use palindrome::ABBA;
 
     #[test]
     fn detects_abba_containment() {
-        assert!(contains_abba(&"abba[arca]zyui".to_string()));
+        assert!(ABBA.find(b"abba[arca]zyui").is_some());
     }
 
     #[test]
     fn ignores_if_no_abba_present() {
-        assert!(!contains_abba(&"abcd[axop]irer".to_string()));
+        assert!(ABBA.find(b"abcd[axop]irer").is_none());
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod validate {
 
-    use part_a_validator::PartAValidator;
+    use part_a_validator::{ PartAValidator, TlsValidator };
+    use palindrome::Palindrome;
+    use evidence::{ Evidence, Found, Section };
     use ip_address::{ IPAddress, IPAddressValidator };
 
     #[test]
@@ -107,4 +124,37 @@ mod validate {
         }
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    fn it_should_explain_the_verdict() {
+        let validator = PartAValidator{};
+
+        let accepted : IPAddress = "xyz[qrst]oxxo".parse().unwrap();
+        let explanation = validator.validate_explained(&accepted);
+        assert!(explanation.valid);
+        assert_eq!(explanation.evidence, vec![Evidence::Abba(Found {
+            text: "oxxo".to_string(), section: Section::Segment, index: 1, offset: 0, position: 9,
+        })]);
+
+        let rejected : IPAddress = "abba[qwwqe]xyyx".parse().unwrap();
+        let explanation = validator.validate_explained(&rejected);
+        assert!(!explanation.valid);
+        assert_eq!(explanation.to_string(), "no: ABBA qwwq in hypernet 0 at offset 0 (byte 5)");
+
+        let none : IPAddress = "abcd[efgh]ijkl".parse().unwrap();
+        assert_eq!(validator.validate_explained(&none).to_string(), "no: no ABBA in any segment");
+    }
+
+    #[test]
+    fn it_should_accept_longer_markers() {
+        let validator = TlsValidator(Palindrome::new(5).unwrap());
+        let accepted : IPAddress = "xyabcbaz[qrst]abba".parse().unwrap();
+        let rejected : IPAddress = "xyabcbaz[qrxyxrq]abba".parse().unwrap();
+
+        assert!(validator.validate(&accepted));
+        assert!(!validator.validate(&rejected));
+        assert_eq!(validator.validate_explained(&accepted).to_string(), "yes: ABBA abcba in segment 0 at offset 2 (byte 2)");
+        // with ABBA markers only the abba segment counts
+        assert!(PartAValidator.validate(&rejected));
+    }
+}
@@ -1,78 +1,97 @@
+use evidence::{ Evidence, Explanation, Section };
 use ip_address::{ IPAddress, IPAddressValidator };
+use palindrome::{ Palindrome, ABA };
 
-pub struct PartBValidator;
-impl IPAddressValidator for PartBValidator {
+// supports SSL: a marker in a segment, with its counterpart in a hypernet
+pub struct SslValidator(pub Palindrome);
+impl IPAddressValidator for SslValidator {
     fn validate(&self, ip_address: &IPAddress) -> bool {
+        let marker = &self.0;
 
-        // convert segments to ABAs
-        let abas : Vec<String> = ip_address.segments.iter().flat_map(|s| {
-            let chars: Vec<String> = s.chars().map(|c| c.to_string()).collect();
-            let abas: Vec<String> = chars.windows(3)
-                .map(|s| s.join(""))
-                .filter(|s| { is_aba(s.as_str()) })
-                .collect();
-
-            abas
-        }).collect();
+        ip_address.segments.iter().any(|s| {
+            let s = s.as_bytes();
+            marker.positions(s).any(|offset| {
+                let window = &s[offset..offset + marker.len];
+                ip_address.hypernets.iter().any(|h| marker.find_counterpart(window, h.as_bytes()).is_some())
+            })
+        })
+    }
 
-        // early return if no ABAs are found
-        if abas.is_empty() {
-            return false;
+    fn validate_explained(&self, ip_address: &IPAddress) -> Explanation {
+        let marker = &self.0;
+        let mut unmatched = Vec::new();
+
+        for (index, s) in ip_address.segments.iter().enumerate() {
+            for offset in marker.positions(s.as_bytes()) {
+                let window = &s.as_bytes()[offset..offset + marker.len];
+                let aba = ip_address.found(Section::Segment, index, offset, marker.len);
+
+                let hypernet = ip_address.hypernets.iter().enumerate()
+                    .filter_map(|(index, h)| marker.find_counterpart(window, h.as_bytes()).map(|offset| (index, offset)))
+                    .next();
+
+                match hypernet {
+                    Some((index, offset)) => {
+                        let bab = ip_address.found(Section::Hypernet, index, offset, marker.len);
+                        return Explanation::new(true, vec![Evidence::AbaBab { aba, bab }]);
+                    }
+                    None => unmatched.push(Evidence::UnmatchedAba(aba)),
+                }
+            }
         }
 
-        // convert ABAs to BABs
-        let babs: Vec<String> = abas.iter().map(|s| get_bab(s.as_str())).collect();
-
-        // does BAB exist in hypernets?
-        babs.iter().any(|b|{
-            ip_address.hypernets.iter().any(|h| {
-                h.contains(b)
-            })
-        })
+        if unmatched.is_empty() {
+            unmatched.push(Evidence::Missing("no ABA in any segment"));
+        }
+        Explanation::new(false, unmatched)
     }
 }
 
-// check if three letter sequence if ABA
-fn is_aba(s: &str) -> bool {
-    s[0..1] == s[2..3] && s[0..1] != s[1..2]
-}
+// the puzzle's SSL, with ABA markers
+pub struct PartBValidator;
+impl IPAddressValidator for PartBValidator {
+    fn validate(&self, ip_address: &IPAddress) -> bool {
+        SslValidator(ABA).validate(ip_address)
+    }
 
-// accept a ABA and generate its BAB
-fn get_bab(aba: &str) -> String {
-    let bab: [&str;3] = [&aba[1..2], &aba[0..1], &aba[1..2]];
-    bab.join("")
+    fn validate_explained(&self, ip_address: &IPAddress) -> Explanation {
+        SslValidator(ABA).validate_explained(ip_address)
+    }
 }
 
 #[cfg(test)]
 mod is_aba {
-    use part_b_validator::is_aba;
+    use palindrome::ABA;
 
     #[test]
     fn it_should_return_true_if_is_aba() {
-        assert!(is_aba("mem"));
-        assert!(is_aba("ioi"));
+        assert!(ABA.matches(b"mem"));
+        assert!(ABA.matches(b"ioi"));
     }
 
     #[test]
     fn it_should_return_false_if_invalid() {
-        assert!(!is_aba("ooo"));
-        assert!(!is_aba("qqq"));
+        assert!(!ABA.matches(b"ooo"));
+        assert!(!ABA.matches(b"qqq"));
     }
 }
 
 #[cfg(test)]
 mod get_bab {
-    use part_b_validator::get_bab;
+    use palindrome::ABA;
 
     #[test]
     fn it_should_generate_bab_for_aba() {
-        assert_eq!(get_bab("bab"), "aba");
+        assert!(ABA.is_counterpart(b"bab", b"aba"));
+        assert!(!ABA.is_counterpart(b"bab", b"bab"));
     }
 }
 
 #[cfg(test)]
 mod validate {
-    use part_b_validator::PartBValidator;
+    use part_b_validator::{ PartBValidator, SslValidator };
+    use palindrome::Palindrome;
+    use evidence::{ Evidence, Found, Section };
     use ip_address::{ IPAddress, IPAddressValidator };
 
     #[test]
@@ -101,4 +120,37 @@ mod validate {
         }
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    fn it_should_explain_the_verdict() {
+        let validator = PartBValidator{};
+
+        let accepted : IPAddress = "zazbz[bzb]cdb".parse().unwrap();
+        let explanation = validator.validate_explained(&accepted);
+        assert!(explanation.valid);
+        assert_eq!(explanation.to_string(), "yes: ABA zbz in segment 0 at offset 2 (byte 2), BAB bzb in hypernet 0 at offset 0 (byte 6)");
+
+        let rejected : IPAddress = "xyx[xyx]xyx".parse().unwrap();
+        let explanation = validator.validate_explained(&rejected);
+        assert!(!explanation.valid);
+        assert_eq!(explanation.evidence, vec![
+            Evidence::UnmatchedAba(Found { text: "xyx".to_string(), section: Section::Segment, index: 0, offset: 0, position: 0 }),
+            Evidence::UnmatchedAba(Found { text: "xyx".to_string(), section: Section::Segment, index: 1, offset: 0, position: 8 }),
+        ]);
+
+        let none : IPAddress = "abcd[bab]efgh".parse().unwrap();
+        assert_eq!(validator.validate_explained(&none).to_string(), "no: no ABA in any segment");
+    }
+
+    #[test]
+    fn it_should_accept_longer_markers() {
+        let validator = SslValidator(Palindrome::new(5).unwrap());
+        let accepted : IPAddress = "xabcbax[zzbacabzz]".parse().unwrap();
+        let rejected : IPAddress = "xabcbax[zzabcbazz]".parse().unwrap();
+
+        assert!(validator.validate(&accepted));
+        assert!(!validator.validate(&rejected));
+        assert_eq!(validator.validate_explained(&accepted).to_string(),
+            "yes: ABA abcba in segment 0 at offset 1 (byte 1), BAB bacab in hypernet 0 at offset 2 (byte 10)");
+    }
+}
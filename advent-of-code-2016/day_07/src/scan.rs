@@ -0,0 +1,242 @@
+use palindrome::{ Palindrome, ABA };
+use std::thread;
+
+// Validates raw lines without building an IPAddress, nothing on this path
+// allocates. Lines are expected to be well formed, as `check` tells, so
+// every `[` and `]` alternates between segments and hypernets.
+
+// byte of the first problem in a line, following the rules of `IPAddress::from_str`
+pub fn check(line: &[u8]) -> Result<(), usize> {
+    if line.is_empty() {
+        return Err(0);
+    }
+
+    let mut open: Option<usize> = None;
+    for (position, &b) in line.iter().enumerate() {
+        if b.is_ascii_lowercase() {
+            continue;
+        }
+
+        match (b, open) {
+            (b'[', None) => open = Some(position),
+            (b']', Some(start)) if start + 1 < position => open = None,
+            (b']', Some(start)) => return Err(start),
+            _ => return Err(position),
+        }
+    }
+
+    open.map_or(Ok(()), Err)
+}
+
+// the segments and hypernets of a line, flagged true for hypernets
+fn runs(line: &[u8]) -> impl Iterator<Item = (bool, &[u8])> {
+    line.split(|&b| b == b'[' || b == b']')
+        .enumerate()
+        .map(|(idx, run)| (idx % 2 == 1, run))
+}
+
+pub fn supports_tls(line: &[u8], marker: &Palindrome) -> bool {
+    let mut found = false;
+    for (hypernet, run) in runs(line) {
+        if marker.find(run).is_some() {
+            if hypernet {
+                return false;
+            }
+            found = true;
+        }
+    }
+    found
+}
+
+pub fn supports_ssl(line: &[u8], marker: &Palindrome) -> bool {
+    if *marker == ABA {
+        return supports_ssl_aba(line);
+    }
+
+    runs(line).filter(|&(hypernet, _)| !hypernet).any(|(_, segment)| {
+        marker.positions(segment).any(|offset| {
+            let window = &segment[offset..offset + marker.len];
+            runs(line).any(|(hypernet, run)| hypernet && marker.find_counterpart(window, run).is_some())
+        })
+    })
+}
+
+// One pass for the puzzle's markers. Every ABA in a segment sets bit b of
+// row a, every BAB in a hypernet sets the same bit, so a row that has a bit
+// set in both is a match.
+fn supports_ssl_aba(line: &[u8]) -> bool {
+    let mut abas = [0u32; 26];
+    let mut babs = [0u32; 26];
+
+    for (hypernet, run) in runs(line) {
+        for window in run.windows(3) {
+            if ABA.matches(window) {
+                let (outer, inner) = ((window[0] - b'a') as usize, (window[1] - b'a') as usize);
+                if hypernet {
+                    babs[inner] |= 1 << outer;
+                } else {
+                    abas[outer] |= 1 << inner;
+                }
+            }
+        }
+    }
+
+    abas.iter().zip(babs.iter()).any(|(aba, bab)| aba & bab != 0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Counts {
+    pub addresses: usize,
+    pub tls: usize,
+    pub ssl: usize,
+}
+
+// a malformed address, with its line number counting from 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub position: usize,
+}
+
+// Counts the addresses in `input`, one per line, that support TLS and SSL
+// with the given markers. Stops at the first malformed line.
+pub fn count(input: &[u8], tls: &Palindrome, ssl: &Palindrome) -> Result<Counts, LineError> {
+    let mut counts = Counts::default();
+    if input.is_empty() {
+        return Ok(counts);
+    }
+
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    for (idx, line) in input.split(|&b| b == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        check(line).map_err(|position| LineError { line: idx + 1, position })?;
+
+        counts.addresses += 1;
+        counts.tls += supports_tls(line, tls) as usize;
+        counts.ssl += supports_ssl(line, ssl) as usize;
+    }
+
+    Ok(counts)
+}
+
+// `count` on `threads` chunks of whole lines at the same time
+pub fn count_parallel(input: &[u8], tls: &Palindrome, ssl: &Palindrome, threads: usize) -> Result<Counts, LineError> {
+    let chunks = chunks(input, threads);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks.iter()
+            .map(|&(start, chunk)| scope.spawn(move || (start, count(chunk, tls, ssl))))
+            .collect();
+
+        let mut total = Counts::default();
+        for worker in workers {
+            match worker.join().unwrap() {
+                (_, Ok(counts)) => {
+                    total.addresses += counts.addresses;
+                    total.tls += counts.tls;
+                    total.ssl += counts.ssl;
+                }
+                // line numbers were counted from the start of the chunk
+                (start, Err(err)) => {
+                    let before = input[..start].iter().filter(|&&b| b == b'\n').count();
+                    return Err(LineError { line: before + err.line, ..err });
+                }
+            }
+        }
+        Ok(total)
+    })
+}
+
+// about `count` equal pieces of `input` with their offsets, each ending after a newline
+fn chunks(input: &[u8], count: usize) -> Vec<(usize, &[u8])> {
+    let size = input.len() / count.max(1) + 1;
+    let mut chunks = Vec::new();
+
+    let mut start = 0;
+    while start < input.len() {
+        let end = (start + size).min(input.len());
+        let end = input[end..].iter().position(|&b| b == b'\n').map_or(input.len(), |p| end + p + 1);
+        chunks.push((start, &input[start..end]));
+        start = end;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod bytes {
+    use bench::generate;
+    use ip_address::{ IPAddress, IPAddressValidator };
+    use palindrome::{ Palindrome, ABA, ABBA };
+    use part_a_validator::{ PartAValidator, TlsValidator };
+    use part_b_validator::{ PartBValidator, SslValidator };
+    use scan::{ check, count, count_parallel, supports_ssl, supports_tls, Counts, LineError };
+
+    const ADDRESSES: &[&str] = &[
+        "abba[mnop]qrst", "abcd[bddb]xyyx", "aaaa[qwer]tyui", "ioxxoj[asdfgh]zxcvbn",
+        "aba[bab]xyz", "xyx[xyx]xyx", "aaa[kek]eke", "zazbz[bzb]cdb",
+        "xyabcbaz[qrst]abba", "xabcbax[zzbacabzz]",
+    ];
+
+    #[test]
+    fn it_agrees_with_the_validators() {
+        for marker in 3..7 {
+            let marker = Palindrome::new(marker).unwrap();
+            for address in ADDRESSES {
+                let parsed: IPAddress = address.parse().unwrap();
+                assert_eq!(supports_tls(address.as_bytes(), &marker), TlsValidator(marker).validate(&parsed), "{}", address);
+                assert_eq!(supports_ssl(address.as_bytes(), &marker), SslValidator(marker).validate(&parsed), "{}", address);
+            }
+        }
+
+        for address in ADDRESSES {
+            let parsed: IPAddress = address.parse().unwrap();
+            assert_eq!(supports_tls(address.as_bytes(), &ABBA), PartAValidator.validate(&parsed));
+            assert_eq!(supports_ssl(address.as_bytes(), &ABA), PartBValidator.validate(&parsed));
+        }
+    }
+
+    #[test]
+    fn it_agrees_on_generated_addresses() {
+        for line in generate(2000).lines() {
+            let parsed: IPAddress = line.parse().unwrap();
+            assert_eq!(supports_tls(line.as_bytes(), &ABBA), PartAValidator.validate(&parsed), "{}", line);
+            assert_eq!(supports_ssl(line.as_bytes(), &ABA), PartBValidator.validate(&parsed), "{}", line);
+        }
+    }
+
+    #[test]
+    fn it_checks_like_the_parser() {
+        for address in &["", "abc]def", "abc[def", "ab[cd]e[f", "ab[]cd", "ab[c[d]e]f", "abC", "ab cd", "ab[cd]]"] {
+            let err = address.parse::<IPAddress>().unwrap_err();
+            assert_eq!(check(address.as_bytes()), Err(err.position), "{:?}", address);
+        }
+        assert_eq!(check(b"[ab]cd[ef]"), Ok(()));
+    }
+
+    #[test]
+    fn it_counts_lines() {
+        let input = ADDRESSES.join("\n") + "\n";
+        let expected = Counts { addresses: 10, tls: 3, ssl: 3 };
+        assert_eq!(count(input.as_bytes(), &ABBA, &ABA), Ok(expected));
+        assert_eq!(count(input.replace('\n', "\r\n").as_bytes(), &ABBA, &ABA), Ok(expected));
+        assert_eq!(count(b"", &ABBA, &ABA), Ok(Counts::default()));
+
+        for threads in 1..12 {
+            assert_eq!(count_parallel(input.as_bytes(), &ABBA, &ABA, threads), Ok(expected), "{} threads", threads);
+        }
+    }
+
+    #[test]
+    fn it_reports_the_malformed_line() {
+        let input = ADDRESSES.join("\n") + "\nab[cd\n" + &ADDRESSES.join("\n");
+        let err = LineError { line: 11, position: 2 };
+        assert_eq!(count(input.as_bytes(), &ABBA, &ABA), Err(err));
+        for threads in 1..12 {
+            assert_eq!(count_parallel(input.as_bytes(), &ABBA, &ABA, threads), Err(err), "{} threads", threads);
+        }
+
+        assert_eq!(count(b"abba\n\nabba", &ABBA, &ABA), Err(LineError { line: 2, position: 0 }));
+    }
+}
+
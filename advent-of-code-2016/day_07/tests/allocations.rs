@@ -0,0 +1,54 @@
+// The byte scan and the puzzle's validators promise not to allocate.
+// Checking that takes a counting global allocator, which gets its own test
+// binary so every other test keeps the system one.
+
+extern crate day_7;
+
+use day_7::ip_address::{ IPAddress, IPAddressValidator };
+use day_7::palindrome::{ ABA, ABBA };
+use day_7::part_a_validator::PartAValidator;
+use day_7::part_b_validator::PartBValidator;
+use day_7::scan::count;
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::cell::Cell;
+
+// allocations made by the current thread, other tests run on their own threads
+thread_local!(static ALLOCATIONS: Cell<usize> = const { Cell::new(0) });
+
+struct Counting;
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[test]
+fn it_does_not_allocate() {
+    let input = "abba[mnop]qrst\naba[bab]xyz\nzazbz[bzb]cdb\nabcd[efgh]ijkl\n".repeat(100);
+
+    let before = ALLOCATIONS.with(|a| a.get());
+    let counts = count(input.as_bytes(), &ABBA, &ABA).unwrap();
+    assert_eq!(ALLOCATIONS.with(|a| a.get()), before);
+    assert_eq!(counts.tls, 100);
+}
+
+#[test]
+fn its_validators_do_not_allocate() {
+    let addresses: Vec<IPAddress> = ["abba[mnop]qrst", "aba[bab]xyz", "zazbz[bzb]cdb", "abcd[efgh]ijkl"].iter()
+        .map(|a| a.parse().unwrap())
+        .collect();
+
+    let before = ALLOCATIONS.with(|a| a.get());
+    let tls = addresses.iter().filter(|a| PartAValidator.validate(a)).count();
+    let ssl = addresses.iter().filter(|a| PartBValidator.validate(a)).count();
+    assert_eq!(ALLOCATIONS.with(|a| a.get()), before);
+    assert_eq!((tls, ssl), (1, 2));
+}